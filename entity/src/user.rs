@@ -16,31 +16,51 @@ impl EntityName for Entity {
     }
 }
 
+// Serializes the identifiers only; the secret columns are skipped
 #[derive(Clone, Debug, PartialEq, Eq, DeriveModel, DeriveActiveModel, Serialize)]
 // #[sea_orm(table_name = "user")]
 pub struct Model {
     // #[sea_orm(primary_key)]
+    pub id: Uuid,  // random (v4), the only identifier other tables and logs may see
+    #[serde(skip)]
     pub private_key: String,
     // #[sea_orm(unique)]
     pub google_id: Option<String>,  // The "sub" value returned by Google
     // #[sea_orm(unique)]
     pub github_id: Option<String>,  // https://api.github.com/users/Hecate2 -> id
+    #[serde(skip)]
+    pub hd_seed: Option<String>,  // hex of the BIP-32 seed; None for accounts made before HD derivation
+    #[serde(skip)]
+    pub mnemonic: Option<String>,  // BIP-39 words of hd_seed, without the passphrase
+    #[serde(skip)]
+    pub data_key: Option<String>,  // wrapped key encrypting the columns above; None for plaintext rows
+}
+
+impl Model {
+    // Number of OAuth identities that can sign in to this account
+    pub fn linked_identities(&self) -> usize {
+        [&self.google_id, &self.github_id].iter().filter(|id| id.is_some()).count()
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
 pub enum Column {
+    Id,
     PrivateKey,
     GoogleId,
     GithubId,
+    HdSeed,
+    Mnemonic,
+    DataKey,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
 pub enum PrimaryKey {
-    PrivateKey,
+    Id,
 }
 
 impl PrimaryKeyTrait for PrimaryKey {
-    type ValueType = String;
+    type ValueType = Uuid;
     fn auto_increment() -> bool {
         false
     }
@@ -54,9 +74,13 @@ impl ColumnTrait for Column {
 
     fn def(&self) -> ColumnDef {
         match self {
+            Self::Id => ColumnType::Uuid.def(),
             Self::PrivateKey => ColumnType::String(None).def(),
             Self::GoogleId => ColumnType::String(None).def().indexed().unique().nullable(),
             Self::GithubId => ColumnType::String(None).def().indexed().unique().nullable(),
+            Self::HdSeed => ColumnType::String(None).def().nullable(),
+            Self::Mnemonic => ColumnType::String(None).def().nullable(),
+            Self::DataKey => ColumnType::String(None).def().nullable(),
         }
     }
 }
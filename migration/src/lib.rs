@@ -1,7 +1,11 @@
 pub use sea_orm_migration::prelude::*;
 
-// mod m20220101_000001_create_table;
 mod m20240320_092624_create_user_table;
+mod m20261018_000000_create_account_table;
+mod m20261018_000001_add_hd_seed;
+mod m20261018_000002_add_mnemonic;
+mod m20261018_000003_add_data_key;
+mod m20261018_000004_add_user_id;
 
 pub struct Migrator;
 
@@ -9,8 +13,12 @@ pub struct Migrator;
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
-            // Box::new(m20220101_000001_create_table::Migration),
             Box::new(m20240320_092624_create_user_table::Migration),
+            Box::new(m20261018_000000_create_account_table::Migration),
+            Box::new(m20261018_000001_add_hd_seed::Migration),
+            Box::new(m20261018_000002_add_mnemonic::Migration),
+            Box::new(m20261018_000003_add_data_key::Migration),
+            Box::new(m20261018_000004_add_user_id::Migration),
         ]
     }
 }
@@ -1,34 +1,39 @@
 use sea_orm_migration::prelude::*;
 
+// The user table as first deployed, keyed by the private key
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-
         manager
             .create_table(
                 Table::create()
                     .table(User::Table)
                     .if_not_exists()
-                    .col(
-                        ColumnDef::new(User::Id)
-                            .integer()
-                            .not_null()
-                            .auto_increment()
-                            .primary_key(),
-                    )
-                    .col(ColumnDef::new(User::Name).string().not_null())
-                    .col(ColumnDef::new(User::Email).string().not_null().unique_key())
-                    .col(ColumnDef::new(User::Password).string().not_null())
+                    .col(ColumnDef::new(User::PrivateKey).string().not_null().primary_key())
+                    .col(ColumnDef::new(User::GoogleId).string().null().unique_key())
+                    .col(ColumnDef::new(User::GithubId).string().null().unique_key())
                     .to_owned(),
             )
-            .await
+            .await?;
+        for column in [User::GoogleId, User::GithubId] {
+            manager
+                .create_index(
+                    Index::create()
+                        .name(format!("idx-user-{}", column.to_string()))
+                        .table(User::Table)
+                        .col(column)
+                        .if_not_exists()
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-
         manager
             .drop_table(Table::drop().table(User::Table).to_owned())
             .await
@@ -38,8 +43,7 @@ impl MigrationTrait for Migration {
 #[derive(DeriveIden)]
 enum User {
     Table,
-    Id,
-    Name,
-    Email,
-    Password
+    PrivateKey,
+    GoogleId,
+    GithubId,
 }
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use crate::m20240320_092624_create_user_table;
+
+// m20240320_092624_create_user_table used to be the sea-orm template (name,
+// email, password), which never matched the entity. Where it ran, replace
+// its table, while empty, with the one that migration makes now.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager.has_column("user", "password").await? {
+            return Ok(());
+        }
+        let row = manager
+            .get_connection()
+            .query_one(Statement::from_string(
+                manager.get_database_backend(),
+                r#"SELECT COUNT(*) AS n FROM public."user""#,
+            ))
+            .await?
+            .ok_or(DbErr::Migration("Failed to count users".to_string()))?;
+        if row.try_get::<i64>("", "n")? != 0 {
+            return Err(DbErr::Migration("Table user has the columns of the template migration and rows; move them out first".to_string()));
+        }
+        manager
+            .drop_table(Table::drop().table(User::Table).to_owned())
+            .await?;
+        m20240320_092624_create_user_table::Migration.up(manager).await
+    }
+
+    // The table left is the one m20240320_092624_create_user_table makes, and
+    // its down drops it; the template table is not worth bringing back
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+}
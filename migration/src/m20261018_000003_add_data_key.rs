@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+// Wrapped data key of the rows encrypted at rest
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(ColumnDef::new(User::DataKey).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Without their data keys, encrypted rows could never be decrypted again
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let encrypted = db
+            .query_one(Statement::from_string(
+                manager.get_database_backend(),
+                r#"SELECT 1 FROM public."user" WHERE data_key IS NOT NULL LIMIT 1"#,
+            ))
+            .await?;
+        if encrypted.is_some() {
+            return Err(DbErr::Migration("Some users have encrypted keys, refusing to drop their data keys".to_string()));
+        }
+        manager
+            .alter_table(Table::alter().table(User::Table).drop_column(User::DataKey).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    DataKey,
+}
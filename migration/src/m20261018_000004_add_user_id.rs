@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+// Replaces the private key as primary key with a random `id`.
+// Existing users get random (v4) ids made here rather than by gen_random_uuid(),
+// which needs PostgreSQL 13 or pgcrypto. The migrator runs this in a transaction.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// Column and constraint name of the primary key of public."user"
+async fn primary_key(manager: &SchemaManager<'_>) -> Result<(String, String), DbErr> {
+    let row = manager
+        .get_connection()
+        .query_one(Statement::from_string(
+            manager.get_database_backend(),
+            r#"
+            SELECT kcu.column_name, tc.constraint_name
+            FROM information_schema.table_constraints tc
+            JOIN information_schema.key_column_usage kcu USING (constraint_schema, constraint_name)
+            WHERE tc.constraint_type = 'PRIMARY KEY' AND tc.table_schema = 'public' AND tc.table_name = 'user'"#,
+        ))
+        .await?
+        .ok_or(DbErr::Migration("Table user has no primary key".to_string()))?;
+    Ok((row.try_get("", "column_name")?, row.try_get("", "constraint_name")?))
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let (column, constraint) = primary_key(manager).await?;
+        if column == "id" {
+            return Ok(());
+        }
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        db.execute_unprepared(r#"ALTER TABLE public."user" ADD COLUMN IF NOT EXISTS id uuid"#).await?;
+        let rows = db
+            .query_all(Statement::from_string(backend, r#"SELECT private_key FROM public."user" WHERE id IS NULL"#))
+            .await?;
+        for row in rows {
+            let private_key: String = row.try_get("", "private_key")?;
+            let mut bytes = [0u8; 16];
+            OsRng.fill_bytes(&mut bytes);
+            let id = uuid::Builder::from_random_bytes(bytes).into_uuid();
+            db.execute(Statement::from_sql_and_values(
+                backend,
+                r#"UPDATE public."user" SET id = $1::uuid WHERE private_key = $2"#,
+                [id.to_string().into(), private_key.into()],
+            ))
+            .await?;
+        }
+        db
+            .execute_unprepared(&format!(
+                r#"
+                ALTER TABLE public."user" ALTER COLUMN id SET NOT NULL;
+                ALTER TABLE public."user" DROP CONSTRAINT "{}";
+                ALTER TABLE public."user" ADD PRIMARY KEY (id)"#,
+                constraint
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let (_, constraint) = primary_key(manager).await?;
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                r#"
+                ALTER TABLE public."user" DROP CONSTRAINT "{}";
+                ALTER TABLE public."user" ADD PRIMARY KEY (private_key);
+                ALTER TABLE public."user" DROP COLUMN id"#,
+                constraint
+            ))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::env;
+use std::error::Error;
+use clap::Subcommand;
+use entity::user;
+use sea_orm::{ColumnTrait, Database, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait};
+use sea_orm::prelude::Uuid;
+use sea_orm::sea_query::Expr;
+use crate::crypto::envelope::WrappedKey;
+use crate::kms::{self, KeyManager};
+use crate::kms::local_file::LocalFileKeyManager;
+use crate::utils::constants::DATABASE_URL;
+
+// Maintenance tasks, run as `oauth_account_backend admin <command>`
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    #[command(about = "Create MASTER_KEY_FILE with a new master key, encrypted with MASTER_KEY_PASSPHRASE")]
+    CreateKeyFile,
+    #[command(about = "Add a new current master key version to MASTER_KEY_FILE")]
+    AddMasterKey,
+    #[command(
+        about = "Re-wrap every data key with the current master key version. Safe to interrupt and run again.",
+        long_about = "Re-wrap every data key with the current master key version. Safe to interrupt and run again.\n\n\
+            Deploy the new version to every server first: append it to MASTER_KEYS and restart each server, \
+            or add it to the MASTER_KEY_FILE they share with add-master-key. \
+            A server without the version cannot read the records re-wrapped here.",
+    )]
+    RotateKeys {
+        #[arg(long, default_value_t = 100)]
+        batch_size: u64,
+    },
+}
+
+fn key_file_env() -> Result<(String, String), Box<dyn Error>> {
+    let path = env::var("MASTER_KEY_FILE").map_err(|_| "MASTER_KEY_FILE is not set")?;
+    let passphrase = env::var("MASTER_KEY_PASSPHRASE").map_err(|_| "MASTER_KEY_PASSPHRASE is not set")?;
+    Ok((path, passphrase))
+}
+
+pub async fn run(command: AdminCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        AdminCommand::CreateKeyFile => {
+            let (path, passphrase) = key_file_env()?;
+            LocalFileKeyManager::create(&path, &passphrase)?;
+            log::info!("Created {} with master key version 1", path);
+        },
+        // With MASTER_KEYS, append the new version to it on every server instead
+        AdminCommand::AddMasterKey => {
+            let (path, passphrase) = key_file_env()?;
+            let version = LocalFileKeyManager::open(&path, &passphrase)?.add_version()?;
+            log::info!("Added master key version {} to {}. New records use it once servers restart; run rotate-keys to re-wrap existing ones.", version, path);
+        },
+        AdminCommand::RotateKeys { batch_size } => {
+            let db = Database::connect(&*DATABASE_URL).await?;
+            let key_manager = kms::from_env()?;
+            rotate_keys(&db, &*key_manager, batch_size).await?;
+        },
+    }
+    Ok(())
+}
+
+// The same data key, wrapped by the current master key version
+pub async fn rewrap(wrapped: &str, key_manager: &dyn KeyManager) -> Result<String, Box<dyn Error>> {
+    let data_key = key_manager.unwrap(&wrapped.parse::<WrappedKey>()?).await?;
+    Ok(key_manager.wrap(&data_key).await?.to_string())
+}
+
+// Re-wraps the data keys of other master key versions, `batch_size` rows per
+// transaction. Only the data_key column changes, so the server keeps serving;
+// an interrupted run resumes with the rows still left when started again.
+pub async fn rotate_keys(db: &DatabaseConnection, key_manager: &dyn KeyManager, batch_size: u64) -> Result<(), Box<dyn Error>> {
+    let current = key_manager.current_version();
+    let stale = || user::Entity::find()
+        .filter(user::Column::DataKey.is_not_null())
+        .filter(user::Column::DataKey.not_like(format!("{}%", WrappedKey::prefix(current))));
+    let total = stale().count(db).await?;
+    log::info!("{} data keys to re-wrap with master key version {}", total, current);
+
+    let (mut rewrapped, mut failed) = (0, 0);
+    let mut last_id: Option<Uuid> = None;
+    loop {
+        let mut query = stale().order_by_asc(user::Column::Id).limit(batch_size);
+        if let Some(last) = last_id {
+            query = query.filter(user::Column::Id.gt(last));
+        }
+        let users = query.all(db).await?;
+        let Some(last) = users.last() else {
+            break;
+        };
+        last_id = Some(last.id);
+
+        let txn = db.begin().await?;
+        for user in &users {
+            let wrapped = user.data_key.as_deref().unwrap_or_default();
+            match rewrap(wrapped, key_manager).await {
+                Ok(new_wrapped) => {
+                    rewrapped += user::Entity::update_many()
+                        .col_expr(user::Column::DataKey, Expr::value(new_wrapped))
+                        .filter(user::Column::Id.eq(user.id))
+                        .filter(user::Column::DataKey.eq(wrapped))
+                        .exec(&txn)
+                        .await?
+                        .rows_affected;
+                },
+                Err(e) => {
+                    failed += 1;
+                    log::error!("Failed to re-wrap the data key of user {}: {}", user.id, e);
+                },
+            }
+        }
+        txn.commit().await?;
+        log::info!("{}/{} re-wrapped, {} failed", rewrapped, total, failed);
+    }
+    if failed > 0 {
+        return Err(format!("{} data keys could not be re-wrapped", failed).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kms::in_memory::InMemoryKeyManager;
+
+    #[actix_web::test]
+    async fn test_rewrap() {
+        let mut key_manager = InMemoryKeyManager::new();
+        let wrapped = key_manager.wrap(&[0x42; 32]).await.unwrap().to_string();
+        key_manager.add_version();
+        let new_wrapped = rewrap(&wrapped, &key_manager).await.unwrap();
+        assert!(new_wrapped.starts_with(&WrappedKey::prefix(2)));
+        assert_eq!(key_manager.unwrap(&new_wrapped.parse().unwrap()).await.unwrap(), [0x42; 32]);
+        assert!(rewrap("v1.7.AAEC", &key_manager).await.is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::error::Error;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use sha3::{Digest, Sha3_256};
+use crate::crypto::secret_key::legacy_ed25519_seed;
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
+
+const ED25519_SCHEME: u8 = 0x00;
+
+pub struct AptosKeypair {
+    pub secret_key: SigningKey,
+    pub public_key: String,
+    pub address: String,  // 0x || SHA3-256(public key || scheme)
+}
+
+impl AptosKeypair {
+    pub fn new() -> AptosKeypair {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        AptosKeypair::from_keypair(SigningKey::from_bytes(&seed))
+    }
+
+    // Accounts made before HD derivation, from a seed derived from their secret
+    pub fn from_compressed_wif(wif: &str) -> Result<AptosKeypair, Box<dyn Error>> {
+        Self::from_secret_key_slice(&legacy_ed25519_seed(wif, "aptos legacy ed25519 seed")?)
+    }
+
+    pub fn from_secret_key_slice(s: &[u8]) -> Result<AptosKeypair, Box<dyn Error>> {
+        let seed: [u8; 32] = s.try_into().map_err(|_| "ed25519 seed must be 32 bytes")?;
+        Ok(AptosKeypair::from_keypair(SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn from_keypair(secret_key: SigningKey) -> AptosKeypair {
+        let public_key_bytes = secret_key.verifying_key().to_bytes();
+        let mut hasher = Sha3_256::new();
+        hasher.update(public_key_bytes);
+        hasher.update([ED25519_SCHEME]);
+        let address = format!("0x{}", hex::encode(hasher.finalize()));
+
+        let public_key_string: String = public_key_bytes.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join("");
+
+        AptosKeypair{ secret_key, public_key: public_key_string, address }
+    }
+
+    // The fullMessage wallets sign for signMessage (AIP-62), without the optional fields
+    pub fn full_message(message: &str, nonce: &str) -> String {
+        format!("APTOS\nmessage: {}\nnonce: {}", message, nonce)
+    }
+
+    pub fn sign_message(&self, message: &str, nonce: &str) -> [u8; 64] {
+        self.sign(Self::full_message(message, nonce).as_bytes())
+    }
+
+    pub fn verify_message(&self, message: &str, nonce: &str, signature: &[u8]) -> bool {
+        self.verify(Self::full_message(message, nonce).as_bytes(), signature)
+    }
+
+    // SHA3-256("APTOS::RawTransaction") || BCS of the RawTransaction
+    pub fn sign_transaction(&self, raw_transaction: &[u8]) -> [u8; 64] {
+        let mut signing_message = Sha3_256::digest(b"APTOS::RawTransaction").to_vec();
+        signing_message.extend_from_slice(raw_transaction);
+        self.sign(&signing_message)
+    }
+
+    // Plain ed25519 over the bytes
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        self.secret_key.sign(message).to_bytes()
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return false;
+        };
+        self.secret_key.verifying_key().verify(message, &signature).is_ok()
+    }
+}
+
+impl ChainKeypair for AptosKeypair {
+    fn generate(_network: Network) -> AptosKeypair {
+        AptosKeypair::new()
+    }
+
+    fn from_wif(wif: &str, _network: Network) -> Result<AptosKeypair, Box<dyn Error>> {
+        AptosKeypair::from_compressed_wif(wif)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], _network: Network) -> Result<AptosKeypair, Box<dyn Error>> {
+        AptosKeypair::from_secret_key_slice(s)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Ed25519
+    }
+
+    // Petra
+    fn hd_path(account: u32, _network: Network) -> String {
+        format!("m/44'/637'/{}'/0'/0'", account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // 0x hex of the seed, as Petra imports
+    fn export_secret_key(&self) -> String {
+        format!("0x{}", hex::encode(self.secret_key.to_bytes()))
+    }
+
+    // The fullMessage with an empty nonce; dapps that pick one use /sign/aptos/message
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let message = std::str::from_utf8(message).map_err(|_| "Aptos messages must be UTF-8")?;
+        Ok(self.sign_message(message, "").to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        std::str::from_utf8(message).is_ok_and(|message| self.verify_message(message, "", signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // RFC 8032 TEST 1 key
+    fn test_addr() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = AptosKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(keypair.address, "0x63c5215e87770d17b9f4cd47c777e322f4eb152cfd2054c1080fd9d57c48913b");
+    }
+
+    #[test]
+    // HMAC-SHA256 checked against Python's hmac module
+    fn test_legacy_seed() {
+        let keypair = AptosKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        assert_eq!(hex::encode(keypair.secret_key.to_bytes()), "596c4e8d40e850818c88a32a3f9106e5d81de2366eace83fdd05b99bafbeef35");
+    }
+
+    #[test]
+    fn test_sign_message() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = AptosKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(AptosKeypair::full_message("hello", "1"), "APTOS\nmessage: hello\nnonce: 1");
+        assert_eq!(hex::encode(keypair.sign_message("hello", "1")), "5b40d5626efcfc0d33385c54cfb66e022fa63aaa9c445e8ad3f205fb00ec4610c4482d540328636bf46c0ec065b993c4167f8db4e9423dedaaa2a3dcc5a65708");
+    }
+
+    #[test]
+    // Checked against the ed25519 of pyca/cryptography
+    fn test_sign_transaction() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = AptosKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(hex::encode(keypair.sign_transaction(&[1, 2, 3, 4, 5])), "5d495cd6cd1c6aa37d6cc22b72dcc618cab348e70eb9c2cd4ae84de5474b6a97230a810a2206085255fd3ff41fc3c5a1c5f981ad2452d87fe2cb32a3e88eb806");
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = AptosKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&AptosKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
@@ -0,0 +1,113 @@
+// Bech32 (BIP-173) and Bech32m (BIP-350) encoding
+// ref: https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+// ref: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+use std::error::Error;
+
+const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,  // checksum constant 1
+    Bech32m,  // checksum constant 0x2bc830a3
+}
+
+impl Variant {
+    fn constant(&self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ (*v as u32);
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut result: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    result.push(0);
+    result.extend(hrp.bytes().map(|b| b & 31));
+    result
+}
+
+// Regroup bits, e.g. 8-bit bytes into 5-bit words
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value = (1 << to) - 1;
+    let mut result = vec![];
+    for value in data {
+        if (*value as u32) >> from != 0 {
+            return Err("Invalid data for bit conversion".into());
+        }
+        acc = (acc << from) | (*value as u32);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return Err("Invalid padding for bit conversion".into());
+    }
+    Ok(result)
+}
+
+// `data` are 5-bit words
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let mut values = hrp_expand(hrp);
+    values.extend(data);
+    values.extend([0u8; 6]);
+    let checksum = polymod(&values) ^ variant.constant();
+    let mut result = format!("{}1", hrp);
+    for word in data.iter().copied().chain((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8)) {
+        result.push(CHARSET[word as usize] as char);
+    }
+    result
+}
+
+// Bytes regrouped into 5-bit words, as used by Cosmos and most non-Bitcoin chains
+pub fn encode_bytes(hrp: &str, bytes: &[u8], variant: Variant) -> String {
+    encode(hrp, &convert_bits(bytes, 8, 5, true).unwrap(), variant)
+}
+
+// Segwit address: witness version, then the program in 5-bit words.
+// Version 0 uses Bech32, version 1+ (Taproot) uses Bech32m.
+pub fn encode_segwit_address(hrp: &str, witness_version: u8, program: &[u8]) -> String {
+    let mut data = vec![witness_version];
+    data.extend(convert_bits(program, 8, 5, true).unwrap());
+    let variant = if witness_version == 0 { Variant::Bech32 } else { Variant::Bech32m };
+    encode(hrp, &data, variant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // ref: BIP-173 and BIP-350 test vectors
+    fn test_encode_segwit_address() {
+        let program = hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        assert_eq!(encode_segwit_address("bc", 0, &program), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        let program = hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        assert_eq!(encode_segwit_address("bc", 1, &program), "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y");
+        assert_eq!(encode("a", &[], Variant::Bech32), "a12uel5l");
+        assert_eq!(encode("a", &[], Variant::Bech32m), "a1lqfn3a");
+    }
+}
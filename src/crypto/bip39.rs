@@ -0,0 +1,177 @@
+use std::fmt;
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha512};
+use unicode_normalization::UnicodeNormalization;
+
+// ref: https://github.com/bitcoin/bips/blob/master/bip-0039/english.txt
+const ENGLISH: &str = include_str!("bip39_english.txt");
+const PBKDF2_ROUNDS: u32 = 2048;
+
+#[derive(Debug, PartialEq)]
+pub enum MnemonicError {
+    InvalidWordCount(usize),
+    UnknownWord(String),
+    InvalidChecksum,
+}
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MnemonicError::InvalidWordCount(count) => write!(f, "Mnemonic must have 12 or 24 words, not {}", count),
+            MnemonicError::UnknownWord(word) => write!(f, "Mnemonic word {} is not in the BIP-39 English wordlist", word),
+            MnemonicError::InvalidChecksum => write!(f, "Mnemonic checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for MnemonicError {}
+
+fn wordlist() -> Vec<&'static str> {
+    ENGLISH.lines().collect()
+}
+
+// 12 words for 128 bits of entropy, 24 words for 256 bits
+fn entropy_len(word_count: usize) -> Result<usize, MnemonicError> {
+    match word_count {
+        12 => Ok(16),
+        24 => Ok(32),
+        _ => Err(MnemonicError::InvalidWordCount(word_count)),
+    }
+}
+
+// entropy || first len/32 bits of its SHA-256, in groups of 11 bits
+pub fn entropy_to_mnemonic(entropy: &[u8]) -> Result<String, MnemonicError> {
+    let word_count = entropy.len() * 3 / 4;
+    if entropy_len(word_count) != Ok(entropy.len()) {
+        return Err(MnemonicError::InvalidWordCount(word_count));
+    }
+    let words = wordlist();
+    let checksum = Sha256::digest(entropy)[0];
+    let mut bits = entropy.iter()
+        .flat_map(|b| (0..8).rev().map(move |i| (b >> i) & 1))
+        .chain((0..8).rev().map(|i| (checksum >> i) & 1).take(entropy.len() / 4));
+    let mut mnemonic = Vec::with_capacity(word_count);
+    for _ in 0..word_count {
+        let index = bits.by_ref().take(11).fold(0usize, |acc, bit| acc << 1 | bit as usize);
+        mnemonic.push(words[index]);
+    }
+    Ok(mnemonic.join(" "))
+}
+
+// Entropy of a mnemonic, with its words and checksum checked
+pub fn mnemonic_to_entropy(mnemonic: &str) -> Result<Vec<u8>, MnemonicError> {
+    let words = wordlist();
+    let mnemonic: Vec<&str> = mnemonic.split_whitespace().collect();
+    let len = entropy_len(mnemonic.len())?;
+    let mut bits = Vec::with_capacity(mnemonic.len() * 11);
+    for word in mnemonic {
+        let index = words.binary_search(&word).map_err(|_| MnemonicError::UnknownWord(word.to_string()))?;
+        bits.extend((0..11).rev().map(|i| (index >> i) & 1 == 1));
+    }
+    let to_byte = |chunk: &[bool]| chunk.iter().fold(0u8, |acc, bit| acc << 1 | *bit as u8);
+    let entropy: Vec<u8> = bits[..len * 8].chunks(8).map(to_byte).collect();
+    let checksum = to_byte(&bits[len * 8..]);
+    if checksum != Sha256::digest(&entropy)[0] >> (8 - len / 4) {
+        return Err(MnemonicError::InvalidChecksum);
+    }
+    Ok(entropy)
+}
+
+pub fn new_mnemonic(word_count: usize) -> Result<String, MnemonicError> {
+    let mut entropy = vec![0u8; entropy_len(word_count)?];
+    OsRng.fill_bytes(&mut entropy);
+    entropy_to_mnemonic(&entropy)
+}
+
+// Words separated by single spaces, as wallets expect them back
+pub fn normalize_mnemonic(mnemonic: &str) -> Result<String, MnemonicError> {
+    let mnemonic = mnemonic.to_lowercase().split_whitespace().collect::<Vec<&str>>().join(" ");
+    mnemonic_to_entropy(&mnemonic)?;
+    Ok(mnemonic)
+}
+
+// PBKDF2-HMAC-SHA512 of the NFKD mnemonic, salted with "mnemonic" || passphrase.
+// The output is a single SHA-512 block, so only the first PBKDF2 block is needed.
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> [u8; 64] {
+    let password: String = mnemonic.nfkd().collect();
+    let salt: String = format!("mnemonic{}", passphrase).nfkd().collect();
+    let prf = Hmac::<Sha512>::new_from_slice(password.as_bytes()).expect("HMAC takes keys of any length");
+
+    let mut mac = prf.clone();
+    mac.update(salt.as_bytes());
+    mac.update(&1u32.to_be_bytes());
+    let mut u: [u8; 64] = mac.finalize().into_bytes().into();
+    let mut seed = u;
+    for _ in 1..PBKDF2_ROUNDS {
+        let mut mac = prf.clone();
+        mac.update(&u);
+        u = mac.finalize().into_bytes().into();
+        seed.iter_mut().zip(u.iter()).for_each(|(s, b)| *s ^= b);
+    }
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::bitcoin_keypair::BitcoinKeypair;
+    use crate::crypto::chain_keypair::{account_keypair, KeySource};
+    use crate::crypto::ethereum_keypair::EthereumKeypair;
+    use crate::crypto::network::Network;
+
+    #[test]
+    fn test_wordlist() {
+        let words = wordlist();
+        assert_eq!(words.len(), 2048);
+        assert!(words.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(hex::encode(Sha256::digest(ENGLISH)), "2f5eed53a4727b4bf8880d8f3f199efc90e58503646d9ff8eff3a2ed3b24dbda");
+    }
+
+    #[test]
+    // ref: https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+    fn test_vectors() {
+        let vectors = [
+            ("00000000000000000000000000000000",
+             "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+             "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"),
+            ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+             "legal winner thank year wave sausage worth useful legal winner thank yellow",
+             "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607"),
+            ("8080808080808080808080808080808080808080808080808080808080808080",
+             "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless",
+             "c0c519bd0e91a2ed54357d9d1ebef6f5af218a153624cf4f2da911a0ed8f7a09e2ef61af0aca007096df430022f7a2b6fb91661a9589097069720d015e4e982f"),
+            ("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+             "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+             "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad"),
+        ];
+        for (entropy, mnemonic, seed) in vectors {
+            let entropy = hex::decode(entropy).unwrap();
+            assert_eq!(entropy_to_mnemonic(&entropy).unwrap(), mnemonic);
+            assert_eq!(mnemonic_to_entropy(mnemonic).unwrap(), entropy);
+            assert_eq!(hex::encode(mnemonic_to_seed(mnemonic, "TREZOR")), seed);
+        }
+    }
+
+    #[test]
+    fn test_wallet_addresses() {
+        let seed = mnemonic_to_seed("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", "");
+        let source = KeySource::Seed(seed.to_vec());
+        let ethereum = account_keypair::<EthereumKeypair>(&source, 0, Network::Mainnet).unwrap();
+        assert_eq!(ethereum.address, "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+        let bitcoin = account_keypair::<BitcoinKeypair>(&source, 0, Network::Mainnet).unwrap();
+        assert_eq!(bitcoin.address, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+    }
+
+    #[test]
+    fn test_invalid_mnemonic() {
+        assert_eq!(mnemonic_to_entropy("abandon abandon abandon").err(), Some(MnemonicError::InvalidWordCount(3)));
+        assert_eq!(mnemonic_to_entropy("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon").err(), Some(MnemonicError::InvalidChecksum));
+        assert_eq!(mnemonic_to_entropy("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon bitcoin").err(), Some(MnemonicError::UnknownWord("bitcoin".to_string())));
+        assert_eq!(normalize_mnemonic(" Legal winner thank year wave sausage\nworth useful legal winner thank yellow").unwrap(), "legal winner thank year wave sausage worth useful legal winner thank yellow");
+        let mnemonic = new_mnemonic(24).unwrap();
+        assert_eq!(mnemonic_to_entropy(&mnemonic).unwrap().len(), 32);
+        assert!(new_mnemonic(15).is_err());
+    }
+}
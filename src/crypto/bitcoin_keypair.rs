@@ -2,51 +2,138 @@ use std::error::Error;
 use rand::rngs::OsRng;
 use secp256k1;
 use secp256k1::Secp256k1;
-use secp256k1::hashes::{sha256::Hash as Sha256Hash, ripemd160::Hash as Ripemp160Hash, Hash};
-use crate::crypto::secret_key::bytes_32_to_wif;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::hashes::{sha256::Hash as Sha256Hash, sha256d::Hash as Sha256dHash, ripemd160::Hash as Ripemp160Hash, Hash};
+use crate::crypto::secret_key::{bytes_32_to_wif, decode_compressed_wif, DEFAULT_VERSION_BYTE};
+use crate::crypto::bech32::encode_segwit_address;
+use crate::crypto::bitcoin_psbt::write_compact_size;
+use crate::crypto::network::Network;
+use std::collections::BTreeMap;
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
 
-const ADDRESS_VERSION_BYTE_MAINNET: u8 = 0x00;
-const WIF_VERSION_BYTE_MAINNET: u8 = 0x80;
+const MESSAGE_MAGIC: &str = "Bitcoin Signed Message:\n";
 
 pub struct BitcoinKeypair {
+    pub secret_key: secp256k1::SecretKey,
     pub secret_key_compressed_wif: String,
     pub public_key: String,
-    pub address: String,
+    pub address: String,  // legacy P2PKH, 1...
+    pub segwit_address: String,  // P2WPKH, bc1q...
+    pub nested_segwit_address: String,  // P2SH-P2WPKH, 3...
+    pub taproot_address: String,  // P2TR key path only (BIP-86), bc1p...
+}
+
+pub fn base58check(mut payload: Vec<u8>) -> String {
+    let checksum = &Sha256Hash::hash(
+        &Sha256Hash::hash(&payload)
+            .to_byte_array()
+    )[..4];
+    payload.extend(checksum);
+    bs58::encode(payload).into_string()
+}
+
+// RIPEMD160(SHA256(data)), as for public key and script hashes
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemp160Hash::hash(&Sha256Hash::hash(data).to_byte_array()).to_byte_array()
+}
+
+// BIP-340 tagged hash: SHA256(SHA256(tag) || SHA256(tag) || msg)
+pub fn tagged_hash(tag: &str, msg: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256Hash::hash(tag.as_bytes()).to_byte_array();
+    let mut data = tag_hash.to_vec();
+    data.extend(tag_hash);
+    data.extend(msg);
+    Sha256Hash::hash(&data).to_byte_array()
+}
+
+// Q = P + hash_TapTweak(P)G for an output without script tree (BIP-86)
+pub fn taproot_output_key(internal_key: secp256k1::XOnlyPublicKey) -> secp256k1::XOnlyPublicKey {
+    let secp = Secp256k1::verification_only();
+    let tweak = secp256k1::Scalar::from_be_bytes(tagged_hash("TapTweak", &internal_key.serialize()))
+        .expect("tweak is a hash, overflowing the curve order with negligible probability");
+    internal_key.add_tweak(&secp, &tweak)
+        .expect("tweaked key is infinity with negligible probability").0
+}
+
+// signmessage of Bitcoin Core: ECDSA over
+// sha256d(compact size || magic || compact size || message), 65 bytes
+// header || r || s with header = 31 + recovery id for compressed keys.
+// Shared by the Bitcoin family (Litecoin, Dogecoin), each with its own magic.
+pub fn sign_message_with_magic(secret_key: &secp256k1::SecretKey, magic: &str, message: &[u8]) -> [u8; 65] {
+    let digest = message_digest_with_magic(magic, message);
+    let (recovery_id, signature) = Secp256k1::new().sign_ecdsa_recoverable(&digest, secret_key).serialize_compact();
+    let mut result = [0u8; 65];
+    result[0] = 31 + recovery_id.to_i32() as u8;
+    result[1..].copy_from_slice(&signature);
+    result
+}
+
+// Whether `signature` is a sign_message_with_magic of `message` by `secret_key`,
+// that is, whether the key recovered from it is the key's public key
+pub fn verify_message_with_magic(secret_key: &secp256k1::SecretKey, magic: &str, message: &[u8], signature: &[u8]) -> bool {
+    let Ok(signature) = <&[u8; 65]>::try_from(signature) else {
+        return false;
+    };
+    // 31 to 34, for compressed keys, the only ones signing here
+    let Ok(recovery_id) = RecoveryId::from_i32(signature[0] as i32 - 31) else {
+        return false;
+    };
+    let secp = Secp256k1::new();
+    RecoverableSignature::from_compact(&signature[1..], recovery_id)
+        .and_then(|signature| secp.recover_ecdsa(&message_digest_with_magic(magic, message), &signature))
+        .is_ok_and(|public_key| public_key == secret_key.public_key(&secp))
+}
+
+fn message_digest_with_magic(magic: &str, message: &[u8]) -> secp256k1::Message {
+    let mut data = vec![];
+    write_compact_size(&mut data, magic.len() as u64);
+    data.extend(magic.as_bytes());
+    write_compact_size(&mut data, message.len() as u64);
+    data.extend(message);
+    secp256k1::Message::from_digest(Sha256dHash::hash(&data).to_byte_array())
 }
 
 impl BitcoinKeypair {
-    pub fn new() -> BitcoinKeypair {
+    pub fn new(network: Network) -> BitcoinKeypair {
         let secp = Secp256k1::new();
         let (secret_key, public_key) = secp.generate_keypair(&mut OsRng);
-        return BitcoinKeypair::from_keypair(secret_key, public_key);
+        BitcoinKeypair::from_keypair(secret_key, public_key, network)
     }
 
-    // checksum not checked!
-    pub fn from_compressed_wif(wif: &str) -> Result<BitcoinKeypair, Box<dyn Error>> {
-        let mut secret_key_bytes: [u8; 38] = [0x00; 38];
-        bs58::decode(wif).onto(&mut secret_key_bytes)?;
-        Ok(BitcoinKeypair::from_secret_key_slice(&secret_key_bytes[1..33])?)
+    // Accounts are stored with the mainnet version byte whatever the network,
+    // so it is accepted besides the network's own
+    pub fn from_compressed_wif(wif: &str, network: Network) -> Result<BitcoinKeypair, Box<dyn Error>> {
+        let secret_key_bytes = decode_compressed_wif(wif, &[network.bitcoin_wif_version(), DEFAULT_VERSION_BYTE])?;
+        BitcoinKeypair::from_secret_key_slice(&secret_key_bytes, network)
     }
 
-    pub fn from_secret_key_slice(s: &[u8]) -> Result<BitcoinKeypair, Box<dyn Error>> {
+    pub fn from_secret_key_slice(s: &[u8], network: Network) -> Result<BitcoinKeypair, Box<dyn Error>> {
         let secp = Secp256k1::new();
         let sk = secp256k1::SecretKey::from_slice(s)?;
         let keypair = secp256k1::Keypair::from_secret_key(&secp, &sk);
         let (secret_key, public_key) = (keypair.secret_key(), keypair.public_key());
-        Ok(BitcoinKeypair::from_keypair(secret_key, public_key))
+        Ok(BitcoinKeypair::from_keypair(secret_key, public_key, network))
     }
 
-    pub fn from_keypair(secret_key: secp256k1::SecretKey, public_key: secp256k1::PublicKey) -> BitcoinKeypair {
+    pub fn from_keypair(secret_key: secp256k1::SecretKey, public_key: secp256k1::PublicKey, network: Network) -> BitcoinKeypair {
         let public_key_bytes = public_key.serialize();
-        let pk_sha256 = Sha256Hash::hash(&public_key_bytes).to_byte_array();
-        let mut pk_ripemp160 = Ripemp160Hash::hash(&pk_sha256).to_byte_array().to_vec();
-        pk_ripemp160.insert(0, ADDRESS_VERSION_BYTE_MAINNET);
-        let checksum = &Sha256Hash::hash(
-            &Sha256Hash::hash(&pk_ripemp160)
-                .to_byte_array()
-        )[..4];
-        pk_ripemp160.extend(checksum);
-        let address = bs58::encode(pk_ripemp160).into_string();
+        let pk_ripemp160 = hash160(&public_key_bytes);
+        let mut payload = vec![network.bitcoin_address_version()];
+        payload.extend(pk_ripemp160);
+        let address = base58check(payload);
+
+        let segwit_address = encode_segwit_address(network.bitcoin_bech32_hrp(), 0, &pk_ripemp160);
+
+        let mut redeem_script = vec![0x00, 0x14];  // OP_0 PUSH20
+        redeem_script.extend(pk_ripemp160);
+        let redeem_script_hash = hash160(&redeem_script);
+        let mut payload = vec![network.bitcoin_p2sh_version()];
+        payload.extend(redeem_script_hash);
+        let nested_segwit_address = base58check(payload);
+
+        let output_key = taproot_output_key(public_key.x_only_public_key().0);
+        let taproot_address = encode_segwit_address(network.bitcoin_bech32_hrp(), 1, &output_key.serialize());
 
         let public_key_string: String = public_key_bytes.iter()
             .map(|b| format!("{:02X}", b))
@@ -54,20 +141,143 @@ impl BitcoinKeypair {
             .join("");
 
         let secret_key_bytes = secret_key.secret_bytes().to_vec();
-        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, WIF_VERSION_BYTE_MAINNET);        
+        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, network.bitcoin_wif_version());        
+
+        BitcoinKeypair{ secret_key, secret_key_compressed_wif, public_key: public_key_string, address, segwit_address, nested_segwit_address, taproot_address}
+    }
+
+    // HASH160 of the compressed public key, as in P2PKH and P2WPKH scripts
+    pub fn pubkey_hash(&self) -> [u8; 20] {
+        let public_key = secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &self.secret_key);
+        hash160(&public_key.serialize())
+    }
+
+    pub fn sign_message(&self, message: &[u8]) -> [u8; 65] {
+        sign_message_with_magic(&self.secret_key, MESSAGE_MAGIC, message)
+    }
+
+    pub fn verify_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        verify_message_with_magic(&self.secret_key, MESSAGE_MAGIC, message, signature)
+    }
+}
+
+impl ChainKeypair for BitcoinKeypair {
+    fn generate(network: Network) -> BitcoinKeypair {
+        BitcoinKeypair::new(network)
+    }
+
+    fn from_wif(wif: &str, network: Network) -> Result<BitcoinKeypair, Box<dyn Error>> {
+        BitcoinKeypair::from_compressed_wif(wif, network)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], network: Network) -> Result<BitcoinKeypair, Box<dyn Error>> {
+        BitcoinKeypair::from_secret_key_slice(s, network)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Secp256k1
+    }
+
+    // BIP-44 account level; coin type 1 for all test networks
+    fn hd_path(account: u32, network: Network) -> String {
+        let coin_type = if network == Network::Mainnet { 0 } else { 1 };
+        format!("m/44'/{}'/{}'/0/0", coin_type, account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // WIF, as Bitcoin Core importprivkey
+    fn export_secret_key(&self) -> String {
+        self.secret_key_compressed_wif.clone()
+    }
+
+    fn addresses(&self) -> BTreeMap<&'static str, String> {
+        BTreeMap::from([
+            ("p2pkh", self.address.to_owned()),
+            ("p2wpkh", self.segwit_address.to_owned()),
+            ("p2sh-p2wpkh", self.nested_segwit_address.to_owned()),
+            ("p2tr", self.taproot_address.to_owned()),
+        ])
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_message(message).to_vec())
+    }
 
-        BitcoinKeypair{ secret_key_compressed_wif: secret_key_compressed_wif, public_key: public_key_string, address: address}
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_message(message, signature)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 
     #[test]
     // ref: https://iancoleman.io/bitcoin-key-compression/
     fn test_addr() {
-        let keypair = BitcoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        let keypair = BitcoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", Network::Mainnet).unwrap();
         assert_eq!(keypair.address, "19GKxUAfpozs8RXuknuZBCAKvJw3SiNYcd");
     }
-}
\ No newline at end of file
+
+    #[test]
+    // ref: https://github.com/bitcoin/bips/blob/master/bip-0084.mediawiki#test-vectors
+    fn test_segwit_addr() {
+        let keypair = BitcoinKeypair::from_compressed_wif("KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d", Network::Mainnet).unwrap();
+        assert_eq!(keypair.segwit_address, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let keypair = BitcoinKeypair::from_secret_key_slice(&one, Network::Mainnet).unwrap();
+        assert_eq!(keypair.segwit_address, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        assert_eq!(keypair.nested_segwit_address, "3JvL6Ymt8MVWiCNHC7oWU6nLeHNJKLZGLN");
+    }
+
+    #[test]
+    // ref: https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki#examples
+    fn test_testnet_addr() {
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let keypair = BitcoinKeypair::from_secret_key_slice(&one, Network::Testnet).unwrap();
+        assert_eq!(keypair.address, "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r");
+        assert_eq!(keypair.segwit_address, "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx");
+        assert!(keypair.nested_segwit_address.starts_with('2'));
+        assert!(keypair.taproot_address.starts_with("tb1p"));
+        assert!(keypair.secret_key_compressed_wif.starts_with('c'));
+        let keypair = BitcoinKeypair::from_secret_key_slice(&one, Network::Regtest).unwrap();
+        assert!(keypair.segwit_address.starts_with("bcrt1q"));
+    }
+
+    #[test]
+    // ref: https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki#test-vectors
+    fn test_taproot_addr() {
+        let internal_key = hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115").unwrap();
+        let internal_key = secp256k1::XOnlyPublicKey::from_slice(&internal_key).unwrap();
+        let output_key = taproot_output_key(internal_key);
+        assert_eq!(hex::encode(output_key.serialize()), "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c");
+        assert_eq!(encode_segwit_address("bc", 1, &output_key.serialize()), "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+    }
+
+    #[test]
+    // ref: https://github.com/bitcoin/bitcoin/blob/master/test/functional/rpc_signmessage.py
+    fn test_sign_message() {
+        let keypair = BitcoinKeypair::from_compressed_wif("cUeKHd5orzT3mz8P9pxyREHfsWtVfgsfDjiZZBcjUBAaGk1BTj7N", Network::Testnet).unwrap();
+        let signature = keypair.sign_message(b"This is just a test message");
+        assert_eq!(BASE64.encode(signature), "INbVnW4e6PeRmsv2Qgu8NuopvrVjkcxob+sX8OcZG0SALhWybUjzMLPdAsXI46YZGb0KQTRii+wWIQzRpG/U+S0=");
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = BitcoinKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&BitcoinKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
@@ -0,0 +1,483 @@
+// Signer role of BIP-174 partially signed Bitcoin transactions (version 0)
+// ref: https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki
+use std::error::Error;
+use secp256k1::Secp256k1;
+use secp256k1::hashes::{sha256d::Hash as Sha256dHash, Hash};
+use crate::crypto::bitcoin_keypair::BitcoinKeypair;
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+const SIGHASH_ALL: u32 = 0x01;
+
+pub struct TxIn {
+    pub previous_output: ([u8; 32], u32),  // (txid, vout)
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+}
+
+#[derive(PartialEq)]
+pub struct TxOut {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+pub struct Transaction {
+    pub version: u32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+// Key-value pairs in their original order, so unknown fields survive a round trip
+pub type PsbtMap = Vec<(Vec<u8>, Vec<u8>)>;
+
+pub struct Psbt {
+    pub global: PsbtMap,
+    pub inputs: Vec<PsbtMap>,
+    pub outputs: Vec<PsbtMap>,
+    pub unsigned_tx: Transaction,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, n: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = self.position.checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("Unexpected end of data")?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Box<dyn Error>> {
+        Ok(self.read(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        Ok(u32::from_le_bytes(self.read(4)?.try_into()?))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Box<dyn Error>> {
+        Ok(u64::from_le_bytes(self.read(8)?.try_into()?))
+    }
+
+    fn read_compact_size(&mut self) -> Result<u64, Box<dyn Error>> {
+        match self.read_u8()? {
+            0xfd => Ok(u16::from_le_bytes(self.read(2)?.try_into()?) as u64),
+            0xfe => Ok(self.read_u32()? as u64),
+            0xff => self.read_u64(),
+            n => Ok(n as u64),
+        }
+    }
+
+    fn read_var_bytes(&mut self) -> Result<&'a [u8], Box<dyn Error>> {
+        let length = usize::try_from(self.read_compact_size()?).map_err(|_| "Length out of range")?;
+        self.read(length)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position == self.data.len()
+    }
+}
+
+pub fn write_compact_size(buffer: &mut Vec<u8>, value: u64) {
+    if value < 0xfd {
+        buffer.push(value as u8);
+    } else if value <= 0xffff {
+        buffer.push(0xfd);
+        buffer.extend((value as u16).to_le_bytes());
+    } else if value <= 0xffffffff {
+        buffer.push(0xfe);
+        buffer.extend((value as u32).to_le_bytes());
+    } else {
+        buffer.push(0xff);
+        buffer.extend(value.to_le_bytes());
+    }
+}
+
+fn write_var_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    write_compact_size(buffer, bytes.len() as u64);
+    buffer.extend(bytes);
+}
+
+impl TxOut {
+    fn parse(reader: &mut Reader) -> Result<TxOut, Box<dyn Error>> {
+        Ok(TxOut{ value: reader.read_u64()?, script_pubkey: reader.read_var_bytes()?.to_vec() })
+    }
+
+    fn serialize(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.value.to_le_bytes());
+        write_var_bytes(buffer, &self.script_pubkey);
+    }
+}
+
+impl Transaction {
+    // Accepts both legacy and segwit serializations; witnesses are skipped
+    fn parse(reader: &mut Reader) -> Result<Transaction, Box<dyn Error>> {
+        let version = reader.read_u32()?;
+        let mut input_count = reader.read_compact_size()?;
+        let segwit = input_count == 0;
+        if segwit {
+            if reader.read_u8()? != 0x01 {
+                return Err("Invalid segwit flag".into());
+            }
+            input_count = reader.read_compact_size()?;
+        }
+        let mut inputs = vec![];
+        for _ in 0..input_count {
+            let txid: [u8; 32] = reader.read(32)?.try_into()?;
+            let vout = reader.read_u32()?;
+            let script_sig = reader.read_var_bytes()?.to_vec();
+            let sequence = reader.read_u32()?;
+            inputs.push(TxIn{ previous_output: (txid, vout), script_sig, sequence });
+        }
+        let output_count = reader.read_compact_size()?;
+        let mut outputs = vec![];
+        for _ in 0..output_count {
+            outputs.push(TxOut::parse(reader)?);
+        }
+        if segwit {
+            for _ in 0..input_count {
+                for _ in 0..reader.read_compact_size()? {
+                    reader.read_var_bytes()?;
+                }
+            }
+        }
+        let lock_time = reader.read_u32()?;
+        Ok(Transaction{ version, inputs, outputs, lock_time })
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Transaction, Box<dyn Error>> {
+        let mut reader = Reader{ data, position: 0 };
+        let tx = Transaction::parse(&mut reader)?;
+        if !reader.is_empty() {
+            return Err("Trailing data after transaction".into());
+        }
+        Ok(tx)
+    }
+
+    // Legacy serialization, without witnesses
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buffer = self.version.to_le_bytes().to_vec();
+        write_compact_size(&mut buffer, self.inputs.len() as u64);
+        for input in &self.inputs {
+            buffer.extend(input.previous_output.0);
+            buffer.extend(input.previous_output.1.to_le_bytes());
+            write_var_bytes(&mut buffer, &input.script_sig);
+            buffer.extend(input.sequence.to_le_bytes());
+        }
+        write_compact_size(&mut buffer, self.outputs.len() as u64);
+        for output in &self.outputs {
+            output.serialize(&mut buffer);
+        }
+        buffer.extend(self.lock_time.to_le_bytes());
+        buffer
+    }
+
+    pub fn txid(&self) -> [u8; 32] {
+        Sha256dHash::hash(&self.serialize()).to_byte_array()
+    }
+
+    // Original sighash algorithm, SIGHASH_ALL only
+    pub fn legacy_sighash(&self, input_index: usize, script_code: &[u8], sighash_type: u32) -> [u8; 32] {
+        let mut buffer = self.version.to_le_bytes().to_vec();
+        write_compact_size(&mut buffer, self.inputs.len() as u64);
+        for (i, input) in self.inputs.iter().enumerate() {
+            buffer.extend(input.previous_output.0);
+            buffer.extend(input.previous_output.1.to_le_bytes());
+            write_var_bytes(&mut buffer, if i == input_index { script_code } else { &[] });
+            buffer.extend(input.sequence.to_le_bytes());
+        }
+        write_compact_size(&mut buffer, self.outputs.len() as u64);
+        for output in &self.outputs {
+            output.serialize(&mut buffer);
+        }
+        buffer.extend(self.lock_time.to_le_bytes());
+        buffer.extend(sighash_type.to_le_bytes());
+        Sha256dHash::hash(&buffer).to_byte_array()
+    }
+
+    // BIP-143 segwit v0 sighash, SIGHASH_ALL only
+    // ref: https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki
+    pub fn segwit_v0_sighash(&self, input_index: usize, script_code: &[u8], value: u64, sighash_type: u32) -> [u8; 32] {
+        let mut prevouts = vec![];
+        let mut sequences = vec![];
+        for input in &self.inputs {
+            prevouts.extend(input.previous_output.0);
+            prevouts.extend(input.previous_output.1.to_le_bytes());
+            sequences.extend(input.sequence.to_le_bytes());
+        }
+        let mut outputs = vec![];
+        for output in &self.outputs {
+            output.serialize(&mut outputs);
+        }
+        let input = &self.inputs[input_index];
+        let mut buffer = self.version.to_le_bytes().to_vec();
+        buffer.extend(Sha256dHash::hash(&prevouts).to_byte_array());
+        buffer.extend(Sha256dHash::hash(&sequences).to_byte_array());
+        buffer.extend(input.previous_output.0);
+        buffer.extend(input.previous_output.1.to_le_bytes());
+        write_var_bytes(&mut buffer, script_code);
+        buffer.extend(value.to_le_bytes());
+        buffer.extend(input.sequence.to_le_bytes());
+        buffer.extend(Sha256dHash::hash(&outputs).to_byte_array());
+        buffer.extend(self.lock_time.to_le_bytes());
+        buffer.extend(sighash_type.to_le_bytes());
+        Sha256dHash::hash(&buffer).to_byte_array()
+    }
+}
+
+fn parse_map(reader: &mut Reader) -> Result<PsbtMap, Box<dyn Error>> {
+    let mut map = vec![];
+    loop {
+        let key = reader.read_var_bytes()?;
+        if key.is_empty() {
+            return Ok(map);
+        }
+        let value = reader.read_var_bytes()?;
+        if map.iter().any(|(k, _)| k == key) {
+            return Err("Duplicate key in PSBT".into());
+        }
+        map.push((key.to_vec(), value.to_vec()));
+    }
+}
+
+fn serialize_map(buffer: &mut Vec<u8>, map: &PsbtMap) {
+    for (key, value) in map {
+        write_var_bytes(buffer, key);
+        write_var_bytes(buffer, value);
+    }
+    buffer.push(0x00);
+}
+
+fn get<'a>(map: &'a PsbtMap, key: &[u8]) -> Option<&'a Vec<u8>> {
+    map.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+impl Psbt {
+    pub fn from_bytes(data: &[u8]) -> Result<Psbt, Box<dyn Error>> {
+        let mut reader = Reader{ data, position: 0 };
+        if reader.read(PSBT_MAGIC.len())? != PSBT_MAGIC {
+            return Err("Not a PSBT".into());
+        }
+        let global = parse_map(&mut reader)?;
+        let unsigned_tx = match get(&global, &[PSBT_GLOBAL_UNSIGNED_TX]) {
+            Some(tx) => Transaction::from_bytes(tx)?,
+            None => return Err("Only PSBT version 0 is supported".into()),
+        };
+        if unsigned_tx.inputs.iter().any(|i| !i.script_sig.is_empty()) {
+            return Err("Unsigned transaction has a scriptSig".into());
+        }
+        let mut inputs = vec![];
+        for _ in 0..unsigned_tx.inputs.len() {
+            inputs.push(parse_map(&mut reader)?);
+        }
+        let mut outputs = vec![];
+        for _ in 0..unsigned_tx.outputs.len() {
+            outputs.push(parse_map(&mut reader)?);
+        }
+        if !reader.is_empty() {
+            return Err("Trailing data after PSBT".into());
+        }
+        Ok(Psbt{ global, inputs, outputs, unsigned_tx })
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buffer = PSBT_MAGIC.to_vec();
+        serialize_map(&mut buffer, &self.global);
+        for map in self.inputs.iter().chain(self.outputs.iter()) {
+            serialize_map(&mut buffer, map);
+        }
+        buffer
+    }
+
+    // The output spent by an input, from its non-witness UTXO after checking the txid
+    fn non_witness_output(&self, index: usize) -> Result<Option<TxOut>, Box<dyn Error>> {
+        let (txid, vout) = self.unsigned_tx.inputs[index].previous_output;
+        let Some(previous_tx) = get(&self.inputs[index], &[PSBT_IN_NON_WITNESS_UTXO]) else {
+            return Ok(None);
+        };
+        let mut previous_tx = Transaction::from_bytes(previous_tx)?;
+        if previous_tx.txid() != txid {
+            return Err(format!("Non-witness UTXO of input {} does not match its txid", index).into());
+        }
+        if (vout as usize) >= previous_tx.outputs.len() {
+            return Err(format!("Input {} spends a missing output", index).into());
+        }
+        Ok(Some(previous_tx.outputs.swap_remove(vout as usize)))
+    }
+
+    fn witness_output(&self, index: usize) -> Result<Option<TxOut>, Box<dyn Error>> {
+        let Some(output) = get(&self.inputs[index], &[PSBT_IN_WITNESS_UTXO]) else {
+            return Ok(None);
+        };
+        let mut reader = Reader{ data: output, position: 0 };
+        let output = TxOut::parse(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(format!("Trailing data after the witness UTXO of input {}", index).into());
+        }
+        Ok(Some(output))
+    }
+
+    // Add partial signatures to every P2PKH and P2WPKH input paying to the keypair.
+    // Returns the indexes of the signed inputs.
+    // P2WPKH inputs need their non-witness UTXO too: a witness UTXO alone can lie
+    // about the amount, and two such PSBTs can trick us into overpaying fees
+    // (CVE-2020-14199). Bitcoin Core and hardware wallets refuse them as well.
+    pub fn sign(&mut self, keypair: &BitcoinKeypair) -> Result<Vec<usize>, Box<dyn Error>> {
+        let secp = Secp256k1::new();
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &keypair.secret_key).serialize();
+        let pubkey_hash = keypair.pubkey_hash();
+        let mut p2pkh = vec![0x76, 0xa9, 0x14];  // OP_DUP OP_HASH160 PUSH20
+        p2pkh.extend(pubkey_hash);
+        p2pkh.extend([0x88, 0xac]);  // OP_EQUALVERIFY OP_CHECKSIG
+        let mut p2wpkh = vec![0x00, 0x14];  // OP_0 PUSH20
+        p2wpkh.extend(pubkey_hash);
+
+        let mut signed = vec![];
+        for index in 0..self.inputs.len() {
+            let non_witness_output = self.non_witness_output(index)?;
+            let witness_output = self.witness_output(index)?;
+            if let (Some(a), Some(b)) = (&non_witness_output, &witness_output) {
+                if a != b {
+                    return Err(format!("Witness UTXO of input {} does not match its non-witness UTXO", index).into());
+                }
+            }
+            let spent_output = match non_witness_output.as_ref().or(witness_output.as_ref()) {
+                Some(o) => o,
+                None => continue,
+            };
+            let sighash_type = match get(&self.inputs[index], &[PSBT_IN_SIGHASH_TYPE]) {
+                Some(t) => u32::from_le_bytes(t.as_slice().try_into()?),
+                None => SIGHASH_ALL,
+            };
+            let sighash = if spent_output.script_pubkey == p2pkh {
+                self.unsigned_tx.legacy_sighash(index, &p2pkh, sighash_type)
+            } else if spent_output.script_pubkey == p2wpkh {
+                if non_witness_output.is_none() {
+                    return Err(format!("Input {} needs its non-witness UTXO to be signed", index).into());
+                }
+                // The script code of P2WPKH is the P2PKH script
+                self.unsigned_tx.segwit_v0_sighash(index, &p2pkh, spent_output.value, sighash_type)
+            } else {
+                continue;
+            };
+            if sighash_type != SIGHASH_ALL {
+                return Err(format!("Input {} requests an unsupported sighash type {}", index, sighash_type).into());
+            }
+            let signature = secp.sign_ecdsa(&secp256k1::Message::from_digest(sighash), &keypair.secret_key);
+            let mut signature = signature.serialize_der().to_vec();
+            signature.push(sighash_type as u8);
+            let mut key = vec![PSBT_IN_PARTIAL_SIG];
+            key.extend(public_key);
+            self.inputs[index].retain(|(k, _)| k != &key);
+            self.inputs[index].push((key, signature));
+            signed.push(index);
+        }
+        Ok(signed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::network::Network;
+
+    // Native P2WPKH example of BIP-143: input 0 spends a P2PK output with the
+    // legacy sighash, input 1 a P2WPKH output
+    // ref: https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki#native-p2wpkh
+    const BIP143_TX: &str = "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000";
+    const SECRET_KEY: &str = "619c335025c7f4012e556c2a58b2506e30b8511b53ade95ea316fd8c3286feb9";
+
+    // Pays 0.01 BTC to P2PKH and 0.02 BTC to P2WPKH of SECRET_KEY
+    const PREVIOUS_TX: &str = "020000000111111111111111111111111111111111111111111111111111111111111111110000000000ffffffff0240420f00000000001976a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac80841e00000000001600141d0f172a0ecb48aee1be1f2687d2963ae33f71a100000000";
+    // Spends both outputs of PREVIOUS_TX
+    const UNSIGNED_TX: &str = "020000000209a50bbbe318a3e56764e99dbabe54c77091caa6ff4df922ac99f40f5f74e2360000000000ffffffff09a50bbbe318a3e56764e99dbabe54c77091caa6ff4df922ac99f40f5f74e2360100000000ffffffff01b09f2d00000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac00000000";
+    const WITNESS_UTXO: &str = "80841e00000000001600141d0f172a0ecb48aee1be1f2687d2963ae33f71a1";
+
+    fn psbt_bytes(inputs: [PsbtMap; 2]) -> Vec<u8> {
+        let mut psbt = PSBT_MAGIC.to_vec();
+        serialize_map(&mut psbt, &vec![(vec![PSBT_GLOBAL_UNSIGNED_TX], hex::decode(UNSIGNED_TX).unwrap())]);
+        for input in &inputs {
+            serialize_map(&mut psbt, input);
+        }
+        serialize_map(&mut psbt, &vec![]);
+        psbt
+    }
+
+    fn non_witness_utxo() -> (Vec<u8>, Vec<u8>) {
+        (vec![PSBT_IN_NON_WITNESS_UTXO], hex::decode(PREVIOUS_TX).unwrap())
+    }
+
+    fn witness_utxo(utxo: &str) -> (Vec<u8>, Vec<u8>) {
+        (vec![PSBT_IN_WITNESS_UTXO], hex::decode(utxo).unwrap())
+    }
+
+    fn keypair() -> BitcoinKeypair {
+        BitcoinKeypair::from_secret_key_slice(&hex::decode(SECRET_KEY).unwrap(), Network::Mainnet).unwrap()
+    }
+
+    #[test]
+    fn test_legacy_sighash() {
+        let tx = Transaction::from_bytes(&hex::decode(BIP143_TX).unwrap()).unwrap();
+        let script_code = hex::decode("2103c9f4836b9a4f77fc0d81f7bcb01b7f1b35916864b9476c241ce9fc198bd25432ac").unwrap();
+        let sighash = tx.legacy_sighash(0, &script_code, SIGHASH_ALL);
+        let secret_key = secp256k1::SecretKey::from_slice(&hex::decode("bbc27228ddcb9209d7fd6f36b02f7dfa6252af40bb2f1cbc7a557da8027ff866").unwrap()).unwrap();
+        let signature = Secp256k1::new().sign_ecdsa(&secp256k1::Message::from_digest(sighash), &secret_key);
+        assert_eq!(hex::encode(signature.serialize_der()), "30450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed");
+    }
+
+    #[test]
+    fn test_segwit_v0_sighash() {
+        let tx = Transaction::from_bytes(&hex::decode(BIP143_TX).unwrap()).unwrap();
+        let script_code = hex::decode("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac").unwrap();
+        let sighash = tx.segwit_v0_sighash(1, &script_code, 600000000, SIGHASH_ALL);
+        assert_eq!(hex::encode(sighash), "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670");
+    }
+
+    #[test]
+    // Checked against an independent Python implementation of both sighashes
+    // that reproduces the BIP-143 vectors above
+    fn test_sign() {
+        let data = psbt_bytes([vec![non_witness_utxo()], vec![non_witness_utxo(), witness_utxo(WITNESS_UTXO)]]);
+        let mut psbt = Psbt::from_bytes(&data).unwrap();
+        assert_eq!(psbt.serialize(), data);
+        assert_eq!(psbt.sign(&keypair()).unwrap(), vec![0, 1]);
+        let signed = Psbt::from_bytes(&psbt.serialize()).unwrap();
+        let (key, signature) = signed.inputs[0].last().unwrap();
+        assert_eq!(hex::encode(key), "02025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357");
+        assert_eq!(hex::encode(signature), "3045022100e8fa37d07afd6553b8067d9b0fb25ba0b6e63645ce0ca971dba93995a5cc610302206fe998a08dcabad7d382b5aee422e8e21430bf09cbee13ba7ec7e250f37f5a7a01");
+        let (_, signature) = signed.inputs[1].last().unwrap();
+        assert_eq!(hex::encode(signature), "3045022100ef6d60339069e6e6ea0ab8ce16fd9cd16f2b9d43679515041e257809868c75f30220675809d84a567846dfed27447ab3b350cebb1762d5c792bfe7c39e34a52cf44d01");
+    }
+
+    #[test]
+    fn test_sign_untrusted_witness_utxo() {
+        // Witness UTXO alone
+        let mut psbt = Psbt::from_bytes(&psbt_bytes([vec![], vec![witness_utxo(WITNESS_UTXO)]])).unwrap();
+        assert!(psbt.sign(&keypair()).is_err());
+        // Witness UTXO with a different amount than the previous transaction
+        let lying = "00e1f505000000001600141d0f172a0ecb48aee1be1f2687d2963ae33f71a1";
+        let mut psbt = Psbt::from_bytes(&psbt_bytes([vec![], vec![non_witness_utxo(), witness_utxo(lying)]])).unwrap();
+        assert!(psbt.sign(&keypair()).is_err());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let data = psbt_bytes([vec![non_witness_utxo()], vec![]]);
+        assert!(Psbt::from_bytes(&data[..data.len() - 1]).is_err());
+        // A key of 2^64 - 1 bytes
+        let mut data = PSBT_MAGIC.to_vec();
+        data.push(0xff);
+        data.extend(u64::MAX.to_le_bytes());
+        assert!(Psbt::from_bytes(&data).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use crate::crypto::aptos_keypair::AptosKeypair;
+use crate::crypto::bitcoin_keypair::BitcoinKeypair;
+use crate::crypto::cosmos_keypair::CosmosKeypair;
+use crate::crypto::dogecoin_keypair::DogecoinKeypair;
+use crate::crypto::ethereum_keypair::EthereumKeypair;
+use crate::crypto::hd::{derive_secret_key, Curve};
+use crate::crypto::litecoin_keypair::LitecoinKeypair;
+use crate::crypto::neo_keypair::NeoKeypair;
+use crate::crypto::network::Network;
+use crate::crypto::solana_keypair::SolanaKeypair;
+use crate::crypto::sui_keypair::SuiKeypair;
+use crate::crypto::tron_keypair::TronKeypair;
+
+// What every chain's keypair offers. Chain specific signing (PSBT,
+// EIP-712, Neo witnesses) stays on the concrete types.
+pub trait ChainKeypair {
+    fn generate(network: Network) -> Self where Self: Sized;
+    fn from_wif(wif: &str, network: Network) -> Result<Self, Box<dyn Error>> where Self: Sized;
+    fn from_secret_key_bytes(s: &[u8], network: Network) -> Result<Self, Box<dyn Error>> where Self: Sized;
+
+    // BIP-44 path of the account at `account`, derived on `hd_curve`
+    fn hd_curve() -> Curve where Self: Sized;
+    fn hd_path(account: u32, network: Network) -> String where Self: Sized;
+
+    fn public_key(&self) -> &str;
+    fn address(&self) -> &str;
+    // Every address type of the key, for chains with more than one
+    fn addresses(&self) -> BTreeMap<&'static str, String> {
+        BTreeMap::new()
+    }
+
+    // The private key in the format the chain's wallets import
+    fn export_secret_key(&self) -> String;
+
+    // Signature of `message` in the chain's off-chain message format, the
+    // one its wallets use for "sign message". Never a transaction signature:
+    // the message is always prefixed or wrapped.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>>;
+    // Whether `signature` is this key's `sign` of `message`
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+}
+
+// Where the keys of a user come from
+pub enum KeySource {
+    // Accounts made before HD derivation: one secret, used as is on ECDSA
+    // chains; ed25519 chains derive a seed of their own from it
+    Legacy(String),
+    // BIP-32 seed; every chain and account index has its own key
+    Seed(Vec<u8>),
+}
+
+// The user's keypair on chain K at account index `account`
+pub fn account_keypair<K: ChainKeypair>(source: &KeySource, account: u32, network: Network) -> Result<K, Box<dyn Error>> {
+    match source {
+        KeySource::Legacy(wif) if account == 0 => K::from_wif(wif, network),
+        KeySource::Legacy(_) => Err("Account indexes other than 0 need an HD wallet".into()),
+        KeySource::Seed(seed) => {
+            let secret_key = derive_secret_key(seed, K::hd_curve(), &K::hd_path(account, network))?;
+            K::from_secret_key_bytes(&secret_key, network)
+        },
+    }
+}
+
+pub type KeypairResult = Result<Box<dyn ChainKeypair>, Box<dyn Error>>;
+
+// A chain in the registry, for picking a chain by name at runtime
+pub struct ChainEntry {
+    pub id: &'static str,
+    pub generate: fn(Network) -> Box<dyn ChainKeypair>,
+    pub from_wif: fn(&str, Network) -> KeypairResult,
+    pub from_secret_key_bytes: fn(&[u8], Network) -> KeypairResult,
+    pub account_keypair: fn(&KeySource, u32, Network) -> KeypairResult,
+}
+
+fn generate_boxed<K: ChainKeypair + 'static>(network: Network) -> Box<dyn ChainKeypair> {
+    Box::new(K::generate(network))
+}
+
+fn from_wif_boxed<K: ChainKeypair + 'static>(wif: &str, network: Network) -> KeypairResult {
+    Ok(Box::new(K::from_wif(wif, network)?))
+}
+
+fn from_secret_key_bytes_boxed<K: ChainKeypair + 'static>(s: &[u8], network: Network) -> KeypairResult {
+    Ok(Box::new(K::from_secret_key_bytes(s, network)?))
+}
+
+fn account_keypair_boxed<K: ChainKeypair + 'static>(source: &KeySource, account: u32, network: Network) -> KeypairResult {
+    Ok(Box::new(account_keypair::<K>(source, account, network)?))
+}
+
+impl ChainEntry {
+    const fn of<K: ChainKeypair + 'static>(id: &'static str) -> ChainEntry {
+        ChainEntry{
+            id,
+            generate: generate_boxed::<K>,
+            from_wif: from_wif_boxed::<K>,
+            from_secret_key_bytes: from_secret_key_bytes_boxed::<K>,
+            account_keypair: account_keypair_boxed::<K>,
+        }
+    }
+}
+
+// Register new chains here
+pub static CHAINS: &[ChainEntry] = &[
+    ChainEntry::of::<BitcoinKeypair>("bitcoin"),
+    ChainEntry::of::<EthereumKeypair>("ethereum"),
+    ChainEntry::of::<NeoKeypair>("neo"),
+    ChainEntry::of::<SolanaKeypair>("solana"),
+    ChainEntry::of::<AptosKeypair>("aptos"),
+    ChainEntry::of::<SuiKeypair>("sui"),
+    ChainEntry::of::<CosmosKeypair>("cosmos"),
+    ChainEntry::of::<TronKeypair>("tron"),
+    ChainEntry::of::<LitecoinKeypair>("litecoin"),
+    ChainEntry::of::<DogecoinKeypair>("dogecoin"),
+];
+
+pub fn chain(id: &str) -> Result<&'static ChainEntry, Box<dyn Error>> {
+    CHAINS.iter().find(|c| c.id == id).ok_or_else(|| format!("Unsupported chain {}", id).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::secret_key::{decode_compressed_wif, DEFAULT_VERSION_BYTE};
+
+    #[test]
+    fn test_registry() {
+        let wif = "KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd";
+        let bitcoin = (chain("bitcoin").unwrap().from_wif)(wif, Network::Mainnet).unwrap();
+        assert_eq!(bitcoin.address(), "19GKxUAfpozs8RXuknuZBCAKvJw3SiNYcd");
+        assert_eq!(bitcoin.addresses().len(), 4);
+        let neo = (chain("neo").unwrap().from_wif)(wif, Network::Mainnet).unwrap();
+        assert_eq!(neo.address(), "NUrR2m4hRyYTdzFseDZzUKLhj5YLJtjEN2");
+        assert!(chain("monero").is_err());
+    }
+
+    #[test]
+    fn test_account_keypair() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let source = KeySource::Seed(seed.clone());
+        for entry in CHAINS {
+            let first = (entry.account_keypair)(&source, 0, Network::Mainnet).unwrap();
+            let second = (entry.account_keypair)(&source, 1, Network::Mainnet).unwrap();
+            assert_ne!(first.address(), second.address(), "{}", entry.id);
+            assert_eq!(first.address(), (entry.account_keypair)(&source, 0, Network::Mainnet).unwrap().address());
+        }
+        let bitcoin = account_keypair::<BitcoinKeypair>(&source, 0, Network::Mainnet).unwrap();
+        let expected = derive_secret_key(&seed, Curve::Secp256k1, "m/44'/0'/0'/0/0").unwrap();
+        assert_eq!(bitcoin.secret_key.secret_bytes(), expected);
+        // Neo keys are P-256 keys now
+        let neo = account_keypair::<NeoKeypair>(&source, 0, Network::Mainnet).unwrap();
+        let expected = derive_secret_key(&seed, Curve::Nist256p1, "m/44'/888'/0'/0/0").unwrap();
+        assert_eq!(neo.secret_key.to_bytes().as_slice(), expected);
+
+        let legacy = KeySource::Legacy("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd".to_string());
+        let bitcoin = account_keypair::<BitcoinKeypair>(&legacy, 0, Network::Mainnet).unwrap();
+        assert_eq!(bitcoin.address, "19GKxUAfpozs8RXuknuZBCAKvJw3SiNYcd");
+        assert!(account_keypair::<BitcoinKeypair>(&legacy, 1, Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_export_secret_key() {
+        let wif = "KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd";
+        let export = |id: &str| (chain(id).unwrap().from_wif)(wif, Network::Mainnet).unwrap().export_secret_key();
+        assert_eq!(export("bitcoin"), wif);
+        assert_eq!(export("neo"), wif);
+        let secret_key = hex::encode(decode_compressed_wif(wif, &[DEFAULT_VERSION_BYTE]).unwrap());
+        assert_eq!(export("ethereum"), format!("0x{}", secret_key));
+        assert_eq!(export("cosmos"), secret_key);
+        // Solana wallets take seed || public key
+        let solana = (chain("solana").unwrap().from_wif)(wif, Network::Mainnet).unwrap();
+        let exported = bs58::decode(solana.export_secret_key()).into_vec().unwrap();
+        assert_eq!(hex::encode(&exported[..32]), "6fccc17fea089c5abd89726b2ca07fd660860abe1b0ddc56e718b68bcfe0dab3");
+        assert_eq!(bs58::encode(&exported[32..]).into_string(), solana.address());
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        for entry in CHAINS {
+            let keypair = (entry.generate)(Network::Mainnet);
+            let signature = keypair.sign(b"hello").unwrap();
+            assert!(keypair.verify(b"hello", &signature), "{}", entry.id);
+            assert!(!keypair.verify(b"hellO", &signature), "{}", entry.id);
+            let other = (entry.generate)(Network::Mainnet);
+            assert!(!other.verify(b"hello", &signature), "{}", entry.id);
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        let wif = "KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd";
+        let bitcoin = BitcoinKeypair::from_compressed_wif(wif, Network::Mainnet).unwrap();
+        let boxed = (chain("bitcoin").unwrap().from_wif)(wif, Network::Mainnet).unwrap();
+        assert_eq!(boxed.sign(b"hello").unwrap(), bitcoin.sign_message(b"hello").to_vec());
+        // Solana off-chain messages can't be empty
+        let solana = (chain("solana").unwrap().from_wif)(wif, Network::Mainnet).unwrap();
+        assert!(solana.sign(b"").is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use rand::rngs::OsRng;
+use secp256k1;
+use secp256k1::Secp256k1;
+use secp256k1::hashes::{sha256::Hash as Sha256Hash, Hash};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::{json, Value};
+use crate::crypto::bech32::{encode_bytes, Variant};
+use crate::crypto::bitcoin_keypair::hash160;
+use crate::crypto::secret_key::{bytes_32_to_wif, decode_compressed_wif, DEFAULT_VERSION_BYTE};
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
+
+pub const DEFAULT_HRP: &str = "cosmos";
+// Chains sharing coin type 118, listed by GET /addresses
+pub const KNOWN_HRPS: [&str; 3] = ["cosmos", "osmo", "juno"];
+
+pub struct CosmosKeypair {
+    pub secret_key: secp256k1::SecretKey,
+    pub secret_key_compressed_wif: String,
+    pub public_key: String,
+    pub address: String,  // bech32 of HASH160 of the compressed public key
+    pub hrp: String,
+}
+
+// cosmos.tx.v1beta1.SignDoc, for SIGN_MODE_DIRECT
+pub struct SignDoc {
+    pub body_bytes: Vec<u8>,
+    pub auth_info_bytes: Vec<u8>,
+    pub chain_id: String,
+    pub account_number: u64,
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_length_delimited(buf: &mut Vec<u8>, field: u8, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;  // proto3 default
+    }
+    buf.push(field << 3 | 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+impl SignDoc {
+    // Protobuf encoding, fields in order and defaults omitted
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_length_delimited(&mut buf, 1, &self.body_bytes);
+        write_length_delimited(&mut buf, 2, &self.auth_info_bytes);
+        write_length_delimited(&mut buf, 3, self.chain_id.as_bytes());
+        if self.account_number != 0 {
+            buf.push(4 << 3);
+            write_varint(&mut buf, self.account_number);
+        }
+        buf
+    }
+}
+
+fn escape_json(json: String) -> String {
+    json.replace('<', "\\u003c").replace('>', "\\u003e").replace('&', "\\u0026")
+}
+
+// Amino JSON sign bytes: sorted keys, no whitespace, and <, >, & escaped as Go does
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<&String, &Value> = map.iter().collect();
+            let fields: Vec<String> = sorted.iter()
+                .map(|(k, v)| format!("{}:{}", escape_json(Value::String(k.to_string()).to_string()), canonical_json(v)))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        },
+        Value::Array(items) => format!("[{}]", items.iter().map(canonical_json).collect::<Vec<String>>().join(",")),
+        _ => escape_json(value.to_string()),
+    }
+}
+
+pub fn adr036_sign_doc(signer: &str, message: &[u8]) -> Value {
+    json!({
+        "account_number": "0",
+        "chain_id": "",
+        "fee": {"amount": [], "gas": "0"},
+        "memo": "",
+        "msgs": [{"type": "sign/MsgSignData", "value": {"data": BASE64.encode(message), "signer": signer}}],
+        "sequence": "0",
+    })
+}
+
+fn check_hrp(hrp: &str) -> Result<(), Box<dyn Error>> {
+    if hrp.is_empty() || hrp.len() > 83 || !hrp.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit()) {
+        return Err(format!("Invalid bech32 prefix {}", hrp).into());
+    }
+    Ok(())
+}
+
+impl CosmosKeypair {
+    pub fn new(hrp: &str) -> Result<CosmosKeypair, Box<dyn Error>> {
+        let secp = Secp256k1::new();
+        let (secret_key, public_key) = secp.generate_keypair(&mut OsRng);
+        CosmosKeypair::from_keypair(secret_key, public_key, hrp)
+    }
+
+    pub fn from_compressed_wif(wif: &str, hrp: &str) -> Result<CosmosKeypair, Box<dyn Error>> {
+        let secret_key_bytes = decode_compressed_wif(wif, &[DEFAULT_VERSION_BYTE])?;
+        CosmosKeypair::from_secret_key_slice(&secret_key_bytes, hrp)
+    }
+
+    pub fn from_secret_key_slice(s: &[u8], hrp: &str) -> Result<CosmosKeypair, Box<dyn Error>> {
+        let secp = Secp256k1::new();
+        let sk = secp256k1::SecretKey::from_slice(s)?;
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &sk);
+        CosmosKeypair::from_keypair(sk, public_key, hrp)
+    }
+
+    pub fn from_keypair(secret_key: secp256k1::SecretKey, public_key: secp256k1::PublicKey, hrp: &str) -> Result<CosmosKeypair, Box<dyn Error>> {
+        check_hrp(hrp)?;
+        let public_key_bytes = public_key.serialize();
+        let address = encode_bytes(hrp, &hash160(&public_key_bytes), Variant::Bech32);
+
+        let public_key_string: String = public_key_bytes.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join("");
+
+        let secret_key_bytes = secret_key.secret_bytes().to_vec();
+        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, DEFAULT_VERSION_BYTE);
+
+        Ok(CosmosKeypair{ secret_key, secret_key_compressed_wif, public_key: public_key_string, address, hrp: hrp.to_string() })
+    }
+
+    // The same account under another chain's prefix
+    pub fn address_with_hrp(&self, hrp: &str) -> Result<String, Box<dyn Error>> {
+        check_hrp(hrp)?;
+        let public_key = secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &self.secret_key);
+        Ok(encode_bytes(hrp, &hash160(&public_key.serialize()), Variant::Bech32))
+    }
+
+    pub fn public_key_bytes(&self) -> [u8; 33] {
+        secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &self.secret_key).serialize()
+    }
+
+    // ECDSA over the SHA-256 of the sign bytes; compact r||s with low s
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        let secp = Secp256k1::new();
+        let digest = secp256k1::Message::from_digest(Sha256Hash::hash(message).to_byte_array());
+        secp.sign_ecdsa(&digest, &self.secret_key).serialize_compact()
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let Ok(signature) = secp256k1::ecdsa::Signature::from_compact(signature) else {
+            return false;
+        };
+        let secp = Secp256k1::new();
+        let digest = secp256k1::Message::from_digest(Sha256Hash::hash(message).to_byte_array());
+        secp.verify_ecdsa(&digest, &signature, &self.secret_key.public_key(&secp)).is_ok()
+    }
+
+    // SIGN_MODE_DIRECT
+    pub fn sign_direct(&self, sign_doc: &SignDoc) -> [u8; 64] {
+        self.sign(&sign_doc.to_bytes())
+    }
+
+    // SIGN_MODE_LEGACY_AMINO_JSON, `sign_doc` is the StdSignDoc
+    pub fn sign_amino(&self, sign_doc: &Value) -> [u8; 64] {
+        self.sign(canonical_json(sign_doc).as_bytes())
+    }
+
+    // ADR-036 arbitrary data, as Keplr's signArbitrary: an amino MsgSignData
+    // with empty chain id, fee and numbers
+    pub fn sign_message(&self, message: &[u8]) -> [u8; 64] {
+        self.sign_amino(&adr036_sign_doc(&self.address, message))
+    }
+
+    pub fn verify_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify(canonical_json(&adr036_sign_doc(&self.address, message)).as_bytes(), signature)
+    }
+}
+
+impl ChainKeypair for CosmosKeypair {
+    fn generate(_network: Network) -> CosmosKeypair {
+        CosmosKeypair::new(DEFAULT_HRP).expect("default prefix is valid")
+    }
+
+    fn from_wif(wif: &str, _network: Network) -> Result<CosmosKeypair, Box<dyn Error>> {
+        CosmosKeypair::from_compressed_wif(wif, DEFAULT_HRP)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], _network: Network) -> Result<CosmosKeypair, Box<dyn Error>> {
+        CosmosKeypair::from_secret_key_slice(s, DEFAULT_HRP)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Secp256k1
+    }
+
+    // Keplr numbers accounts by the address index
+    fn hd_path(account: u32, _network: Network) -> String {
+        format!("m/44'/118'/0'/0/{}", account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // hex, as Keplr imports
+    fn export_secret_key(&self) -> String {
+        hex::encode(self.secret_key.secret_bytes())
+    }
+
+    fn addresses(&self) -> BTreeMap<&'static str, String> {
+        KNOWN_HRPS.iter()
+            .map(|hrp| (*hrp, self.address_with_hrp(hrp).expect("known prefixes are valid")))
+            .collect()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_message(message).to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_message(message, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIF: &str = "KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd";
+
+    #[test]
+    fn test_addr() {
+        let keypair = CosmosKeypair::from_compressed_wif(WIF, "cosmos").unwrap();
+        assert_eq!(keypair.address, "cosmos1t2n3r4txfdy3f8hyfcnwjmxmhwa4u0jhpyzrn9");
+        assert_eq!(keypair.address_with_hrp("osmo").unwrap(), "osmo1t2n3r4txfdy3f8hyfcnwjmxmhwa4u0jhfl3n9h");
+        assert_eq!(CosmosKeypair::from_compressed_wif(WIF, "juno").unwrap().address, "juno1t2n3r4txfdy3f8hyfcnwjmxmhwa4u0jhhkpc5e");
+        assert!(CosmosKeypair::from_compressed_wif(WIF, "Cosmos").is_err());
+    }
+
+    #[test]
+    fn test_sign_direct() {
+        let keypair = CosmosKeypair::from_compressed_wif(WIF, "cosmos").unwrap();
+        let sign_doc = SignDoc{
+            body_bytes: hex::decode("0a0568656c6c6f").unwrap(),
+            auth_info_bytes: hex::decode("1200").unwrap(),
+            chain_id: "cosmoshub-4".to_string(),
+            account_number: 42,
+        };
+        assert_eq!(hex::encode(sign_doc.to_bytes()), "0a070a0568656c6c6f120212001a0b636f736d6f736875622d34202a");
+        assert_eq!(BASE64.encode(keypair.sign_direct(&sign_doc)), "a6Cysy/AaOat9b2CUnDSebzh9aA1BGtKP1p5RKasE8kozNOYSK2Z+7Jdi5F8n7P5N0zJcJHpacK3n32njpdRsA==");
+    }
+
+    #[test]
+    fn test_sign_amino() {
+        let keypair = CosmosKeypair::from_compressed_wif(WIF, "cosmos").unwrap();
+        let sign_doc: Value = serde_json::from_str(r#"{"chain_id": "cosmoshub-4", "account_number": "42", "sequence": "0", "fee": {"amount": [], "gas": "200000"}, "msgs": [], "memo": "a<b"}"#).unwrap();
+        assert_eq!(canonical_json(&sign_doc), r#"{"account_number":"42","chain_id":"cosmoshub-4","fee":{"amount":[],"gas":"200000"},"memo":"a\u003cb","msgs":[],"sequence":"0"}"#);
+        assert_eq!(BASE64.encode(keypair.sign_amino(&sign_doc)), "Y00GheM1hEZhjVKmJxFHPavOA1iQ5s5jXFS8zAhBSClc97VChDM3+GpSGJRuGllqp6VSZvGZ8xEy86y3xfJruQ==");
+    }
+
+    #[test]
+    // Checked against the deterministic ECDSA of OpenSSL
+    fn test_sign_message() {
+        let keypair = CosmosKeypair::from_compressed_wif(WIF, "cosmos").unwrap();
+        assert_eq!(canonical_json(&adr036_sign_doc(&keypair.address, b"hello")), r#"{"account_number":"0","chain_id":"","fee":{"amount":[],"gas":"0"},"memo":"","msgs":[{"type":"sign/MsgSignData","value":{"data":"aGVsbG8=","signer":"cosmos1t2n3r4txfdy3f8hyfcnwjmxmhwa4u0jhpyzrn9"}}],"sequence":"0"}"#);
+        assert_eq!(BASE64.encode(keypair.sign_message(b"hello")), "d4NuGFo/d6Yx63srVRZS4aPJIUfP5waYgu3VmTIIF6MymlgumbiGtn4v6ioyjGxGwP8R5otOkEJ1ifmy5n7Wug==");
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = CosmosKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&CosmosKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
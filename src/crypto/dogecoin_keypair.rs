@@ -0,0 +1,149 @@
+use std::error::Error;
+use rand::rngs::OsRng;
+use secp256k1;
+use secp256k1::Secp256k1;
+use crate::crypto::bitcoin_keypair::{base58check, hash160, sign_message_with_magic, verify_message_with_magic};
+use crate::crypto::secret_key::{bytes_32_to_wif, decode_compressed_wif, DEFAULT_VERSION_BYTE};
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
+
+// P2PKH version and WIF version; Dogecoin has no segwit
+fn versions(network: Network) -> (u8, u8) {
+    match network {
+        Network::Mainnet => (0x1e, 0x9e),
+        Network::Testnet | Network::Regtest => (0x71, 0xf1),
+    }
+}
+
+const MESSAGE_MAGIC: &str = "Dogecoin Signed Message:\n";
+
+pub struct DogecoinKeypair {
+    pub secret_key: secp256k1::SecretKey,
+    pub secret_key_compressed_wif: String,
+    pub public_key: String,
+    pub address: String,  // P2PKH, D...
+}
+
+impl DogecoinKeypair {
+    pub fn new(network: Network) -> DogecoinKeypair {
+        let secp = Secp256k1::new();
+        let (secret_key, public_key) = secp.generate_keypair(&mut OsRng);
+        DogecoinKeypair::from_keypair(secret_key, public_key, network)
+    }
+
+    // Accounts are stored with the Bitcoin mainnet version byte
+    pub fn from_compressed_wif(wif: &str, network: Network) -> Result<DogecoinKeypair, Box<dyn Error>> {
+        let secret_key_bytes = decode_compressed_wif(wif, &[versions(network).1, DEFAULT_VERSION_BYTE])?;
+        DogecoinKeypair::from_secret_key_slice(&secret_key_bytes, network)
+    }
+
+    pub fn from_secret_key_slice(s: &[u8], network: Network) -> Result<DogecoinKeypair, Box<dyn Error>> {
+        let secp = Secp256k1::new();
+        let sk = secp256k1::SecretKey::from_slice(s)?;
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &sk);
+        Ok(DogecoinKeypair::from_keypair(sk, public_key, network))
+    }
+
+    pub fn from_keypair(secret_key: secp256k1::SecretKey, public_key: secp256k1::PublicKey, network: Network) -> DogecoinKeypair {
+        let (p2pkh_version, wif_version) = versions(network);
+        let public_key_bytes = public_key.serialize();
+
+        let mut payload = vec![p2pkh_version];
+        payload.extend(hash160(&public_key_bytes));
+        let address = base58check(payload);
+
+        let public_key_string: String = public_key_bytes.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join("");
+
+        let secret_key_bytes = secret_key.secret_bytes().to_vec();
+        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, wif_version);
+
+        DogecoinKeypair{ secret_key, secret_key_compressed_wif, public_key: public_key_string, address }
+    }
+
+    pub fn sign_message(&self, message: &[u8]) -> [u8; 65] {
+        sign_message_with_magic(&self.secret_key, MESSAGE_MAGIC, message)
+    }
+
+    pub fn verify_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        verify_message_with_magic(&self.secret_key, MESSAGE_MAGIC, message, signature)
+    }
+}
+
+impl ChainKeypair for DogecoinKeypair {
+    fn generate(network: Network) -> DogecoinKeypair {
+        DogecoinKeypair::new(network)
+    }
+
+    fn from_wif(wif: &str, network: Network) -> Result<DogecoinKeypair, Box<dyn Error>> {
+        DogecoinKeypair::from_compressed_wif(wif, network)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], network: Network) -> Result<DogecoinKeypair, Box<dyn Error>> {
+        DogecoinKeypair::from_secret_key_slice(s, network)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Secp256k1
+    }
+
+    fn hd_path(account: u32, network: Network) -> String {
+        let coin_type = if network == Network::Mainnet { 3 } else { 1 };
+        format!("m/44'/{}'/{}'/0/0", coin_type, account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // WIF, as Dogecoin Core importprivkey
+    fn export_secret_key(&self) -> String {
+        self.secret_key_compressed_wif.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_message(message).to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_message(message, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+    #[test]
+    fn test_addr() {
+        let keypair = DogecoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", Network::Mainnet).unwrap();
+        assert_eq!(keypair.address, "DDQRVj7K8Du9fRiWVNu7ixKvoSfLfnnwtV");
+        assert_eq!(keypair.secret_key_compressed_wif, "QQjfrHtJKbqPRmdtwAjkDeusqQvYJnGqBfhaLEuva6gkaWioUmty");
+        let keypair = DogecoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", Network::Testnet).unwrap();
+        assert_eq!(keypair.address, "ncTVDjrE4CMsYQHhXCYZyMvE3K3diKbutJ");
+    }
+
+    #[test]
+    // Checked against an independent Python implementation of signmessage
+    fn test_sign_message() {
+        let keypair = DogecoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", Network::Mainnet).unwrap();
+        assert_eq!(BASE64.encode(keypair.sign_message(b"hello")), "HyM0bGVTItaYZV1pHRAkPggNhe4mZVPNy0XXMkPP8RDeekiRQAV82t7VOZmBqUtgxP6ouBNQUwgkuq79E5mVBGs=");
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = DogecoinKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&DogecoinKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
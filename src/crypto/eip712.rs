@@ -0,0 +1,306 @@
+// EIP-712 typed structured data hashing
+// ref: https://eips.ethereum.org/EIPS/eip-712
+use std::collections::BTreeSet;
+use std::error::Error;
+use serde_json::{Map, Value};
+use sha3::{Digest, Keccak256};
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+fn type_fields<'a>(types: &'a Map<String, Value>, name: &str) -> Result<&'a Vec<Value>, Box<dyn Error>> {
+    match types.get(name).and_then(|t| t.as_array()) {
+        Some(fields) => Ok(fields),
+        None => Err(format!("Unknown type {}", name).into()),
+    }
+}
+
+fn field_name_and_type(field: &Value) -> Result<(&str, &str), Box<dyn Error>> {
+    match (field.get("name").and_then(|n| n.as_str()), field.get("type").and_then(|t| t.as_str())) {
+        (Some(name), Some(t)) => Ok((name, t)),
+        _ => Err("Type field without name or type".into()),
+    }
+}
+
+// "Person[2][]" -> "Person"
+fn base_type(t: &str) -> &str {
+    match t.find('[') {
+        Some(i) => &t[..i],
+        None => t,
+    }
+}
+
+fn find_dependencies(types: &Map<String, Value>, primary_type: &str, found: &mut BTreeSet<String>) -> Result<(), Box<dyn Error>> {
+    let primary_type = base_type(primary_type);
+    if found.contains(primary_type) || !types.contains_key(primary_type) {
+        return Ok(());
+    }
+    found.insert(primary_type.to_string());
+    for field in type_fields(types, primary_type)? {
+        let (_, t) = field_name_and_type(field)?;
+        find_dependencies(types, t, found)?;
+    }
+    Ok(())
+}
+
+// "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+pub fn encode_type(types: &Map<String, Value>, primary_type: &str) -> Result<String, Box<dyn Error>> {
+    let mut dependencies = BTreeSet::new();
+    find_dependencies(types, primary_type, &mut dependencies)?;
+    dependencies.remove(primary_type);
+    let mut result = String::new();
+    for name in std::iter::once(primary_type.to_string()).chain(dependencies) {
+        let fields = type_fields(types, &name)?.iter()
+            .map(|f| field_name_and_type(f).map(|(n, t)| format!("{} {}", t, n)))
+            .collect::<Result<Vec<String>, Box<dyn Error>>>()?;
+        result += &format!("{}({})", name, fields.join(","));
+    }
+    Ok(result)
+}
+
+pub fn type_hash(types: &Map<String, Value>, primary_type: &str) -> Result<[u8; 32], Box<dyn Error>> {
+    Ok(keccak256(encode_type(types, primary_type)?.as_bytes()))
+}
+
+// Big-endian 256-bit word from a JSON number or a decimal/0x-hex string.
+// Negative values are encoded in two's complement.
+pub fn encode_integer(value: &Value) -> Result<[u8; 32], Box<dyn Error>> {
+    let s = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        Value::Bool(b) => (*b as u8).to_string(),
+        _ => return Err(format!("Invalid integer {}", value).into()),
+    };
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(d) => (true, d),
+        None => (false, s.as_str()),
+    };
+    let (radix, digits) = match digits.strip_prefix("0x") {
+        Some(d) => (16, d),
+        None => (10, digits),
+    };
+    if digits.is_empty() {
+        return Err(format!("Invalid integer {}", value).into());
+    }
+    let mut word = [0u8; 32];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix).ok_or(format!("Invalid integer {}", value))?;
+        for byte in word.iter_mut().rev() {
+            let v = (*byte as u32) * radix + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            return Err(format!("Integer overflow {}", value).into());
+        }
+    }
+    if negative {
+        for byte in word.iter_mut() {
+            *byte = !*byte;
+        }
+        for byte in word.iter_mut().rev() {
+            let (v, overflow) = byte.overflowing_add(1);
+            *byte = v;
+            if !overflow {
+                break;
+            }
+        }
+    }
+    Ok(word)
+}
+
+// encode_integer, checked to fit uintN or intN: N bits, and no sign for uintN
+fn encode_sized_integer(t: &str, value: &Value) -> Result<[u8; 32], Box<dyn Error>> {
+    let (signed, bits) = match t.strip_prefix("uint") {
+        Some(bits) => (false, bits),
+        None => (true, &t[3..]),
+    };
+    let bits = match bits {
+        "" => 256,
+        _ => bits.parse::<usize>().map_err(|_| format!("Unknown type {}", t))?,
+    };
+    if bits == 0 || bits > 256 || bits % 8 != 0 {
+        return Err(format!("Unknown type {}", t).into());
+    }
+    let word = encode_integer(value)?;
+    let negative = word != [0; 32] && match value {
+        Value::Number(n) => n.to_string().starts_with('-'),
+        Value::String(s) => s.trim().starts_with('-'),
+        _ => false,
+    };
+    let top = 32 - bits / 8;
+    let fits = if signed {
+        // Sign-extended from bit N-1, with the sign the value was given
+        let fill = if word[top] & 0x80 != 0 { 0xff } else { 0 };
+        word[..top].iter().all(|&b| b == fill) && (fill == 0xff) == negative
+    } else {
+        !negative && word[..top].iter().all(|&b| b == 0)
+    };
+    if !fits {
+        return Err(format!("Invalid {} {}", t, value).into());
+    }
+    Ok(word)
+}
+
+fn decode_hex(value: &Value) -> Result<Vec<u8>, Box<dyn Error>> {
+    match value.as_str() {
+        Some(s) => Ok(hex::decode(s.trim_start_matches("0x"))?),
+        None => Err(format!("Invalid hex {}", value).into()),
+    }
+}
+
+fn encode_value(types: &Map<String, Value>, t: &str, value: &Value) -> Result<[u8; 32], Box<dyn Error>> {
+    if t.ends_with(']') {
+        let item_type = &t[..t.rfind('[').unwrap()];
+        let items = value.as_array().ok_or(format!("Expected array for {}", t))?;
+        let mut encoded = Vec::with_capacity(items.len() * 32);
+        for item in items {
+            encoded.extend(encode_value(types, item_type, item)?);
+        }
+        return Ok(keccak256(&encoded));
+    }
+    if types.contains_key(t) {
+        return hash_struct(types, t, value);
+    }
+    match t {
+        "string" => {
+            let s = value.as_str().ok_or(format!("Expected string, got {}", value))?;
+            Ok(keccak256(s.as_bytes()))
+        },
+        "bytes" => Ok(keccak256(&decode_hex(value)?)),
+        "bool" => match value {
+            Value::Bool(b) => encode_integer(&Value::Bool(*b)),
+            _ => encode_integer(value),
+        },
+        "address" => {
+            let bytes = decode_hex(value)?;
+            if bytes.len() != 20 {
+                return Err(format!("Invalid address {}", value).into());
+            }
+            let mut word = [0u8; 32];
+            word[12..].copy_from_slice(&bytes);
+            Ok(word)
+        },
+        _ if t.starts_with("bytes") => {
+            let bytes = decode_hex(value)?;
+            if bytes.len() > 32 {
+                return Err(format!("Invalid {} {}", t, value).into());
+            }
+            let mut word = [0u8; 32];
+            word[..bytes.len()].copy_from_slice(&bytes);
+            Ok(word)
+        },
+        _ if t.starts_with("uint") || t.starts_with("int") => encode_sized_integer(t, value),
+        _ => Err(format!("Unknown type {}", t).into()),
+    }
+}
+
+pub fn hash_struct(types: &Map<String, Value>, primary_type: &str, data: &Value) -> Result<[u8; 32], Box<dyn Error>> {
+    let mut encoded = type_hash(types, primary_type)?.to_vec();
+    for field in type_fields(types, primary_type)? {
+        let (name, t) = field_name_and_type(field)?;
+        let value = data.get(name).unwrap_or(&Value::Null);
+        encoded.extend(encode_value(types, t, value)?);
+    }
+    Ok(keccak256(&encoded))
+}
+
+// The digest to sign for eth_signTypedData_v4:
+// keccak256(0x19 0x01 || domainSeparator || hashStruct(message))
+pub fn hash_typed_data(typed_data: &Value) -> Result<[u8; 32], Box<dyn Error>> {
+    let types = typed_data.get("types").and_then(|t| t.as_object()).ok_or("No types")?;
+    let primary_type = typed_data.get("primaryType").and_then(|t| t.as_str()).ok_or("No primaryType")?;
+    let domain = typed_data.get("domain").ok_or("No domain")?;
+    let mut encoded = vec![0x19, 0x01];
+    encoded.extend(hash_struct(types, "EIP712Domain", domain)?);
+    if primary_type != "EIP712Domain" {
+        let message = typed_data.get("message").ok_or("No message")?;
+        encoded.extend(hash_struct(types, primary_type, message)?);
+    }
+    Ok(keccak256(&encoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::ethereum_keypair::EthereumKeypair;
+
+    const MAIL: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "Person": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"}
+            ],
+            "Mail": [
+                {"name": "from", "type": "Person"},
+                {"name": "to", "type": "Person"},
+                {"name": "contents", "type": "string"}
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+            "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    #[test]
+    // ref: https://github.com/ethereum/EIPs/blob/master/assets/eip-712/Example.js
+    fn test_hash_typed_data() {
+        let typed_data: Value = serde_json::from_str(MAIL).unwrap();
+        let types = typed_data["types"].as_object().unwrap();
+        assert_eq!(encode_type(types, "Mail").unwrap(), "Mail(Person from,Person to,string contents)Person(string name,address wallet)");
+        assert_eq!(hex::encode(hash_struct(types, "EIP712Domain", &typed_data["domain"]).unwrap()), "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f");
+        assert_eq!(hex::encode(hash_struct(types, "Mail", &typed_data["message"]).unwrap()), "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e");
+        assert_eq!(hex::encode(hash_typed_data(&typed_data).unwrap()), "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+
+        let cow = EthereumKeypair::from_secret_key_slice(&keccak256(b"cow")).unwrap();
+        assert_eq!(cow.address, "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826");
+        let signature = cow.sign_typed_data(&typed_data).unwrap();
+        assert_eq!(hex::encode(signature), "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c");
+    }
+
+    #[test]
+    fn test_encode_integer() {
+        assert_eq!(encode_integer(&Value::from(1)).unwrap()[31], 1);
+        assert_eq!(encode_integer(&Value::from("0x0100")).unwrap()[30], 1);
+        assert_eq!(encode_integer(&Value::from(-1)).unwrap(), [0xff; 32]);
+        assert!(encode_integer(&Value::from("0x1".to_owned() + &"0".repeat(64))).is_err());
+    }
+
+    #[test]
+    fn test_encode_sized_integer() {
+        assert_eq!(encode_sized_integer("uint8", &Value::from(255)).unwrap()[31], 0xff);
+        assert!(encode_sized_integer("uint8", &Value::from(256)).is_err());
+        assert!(encode_sized_integer("uint16", &Value::from("0x10000")).is_err());
+        assert!(encode_sized_integer("uint256", &Value::from(-1)).is_err());
+        assert!(encode_sized_integer("uint8", &Value::from("-0x1")).is_err());
+        assert!(encode_sized_integer("uint7", &Value::from(1)).is_err());
+        assert!(encode_sized_integer("uint264", &Value::from(1)).is_err());
+    }
+
+    #[test]
+    fn test_encode_signed_sized_integer() {
+        assert_eq!(encode_sized_integer("int8", &Value::from(127)).unwrap()[31], 0x7f);
+        assert_eq!(encode_sized_integer("int8", &Value::from(-128)).unwrap()[31], 0x80);
+        assert!(encode_sized_integer("int8", &Value::from(128)).is_err());
+        assert!(encode_sized_integer("int8", &Value::from(-129)).is_err());
+        assert_eq!(encode_sized_integer("int256", &Value::from(-1)).unwrap(), [0xff; 32]);
+        assert!(encode_sized_integer("int256", &Value::from("0x8".to_owned() + &"0".repeat(63))).is_err());
+        assert!(encode_sized_integer("int256", &Value::from("-0x".to_owned() + &"f".repeat(64))).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::str::FromStr;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+
+// Envelope encryption: every record has its own random data key, stored
+// wrapped (encrypted) by a master key that never touches the database.
+// See the kms module of the server for where master keys live.
+//
+// Wrapped data key: v1.<master key version>.<base64 of the key manager's ciphertext>
+// Encrypted secret: v1.<base64 of nonce || ciphertext || tag>, AES-256-GCM
+const FORMAT_VERSION: &str = "v1";
+
+#[derive(Debug, PartialEq)]
+pub enum EnvelopeError {
+    InvalidFormat,
+    UnsupportedVersion(String),
+    InvalidKeyLength(usize),
+    DecryptionFailed,
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvelopeError::InvalidFormat => write!(f, "Ciphertext is malformed"),
+            EnvelopeError::UnsupportedVersion(version) => write!(f, "Unsupported ciphertext version {}", version),
+            EnvelopeError::InvalidKeyLength(len) => write!(f, "Data keys are 32 bytes, not {}", len),
+            EnvelopeError::DecryptionFailed => write!(f, "Decryption failed"),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+fn aes_key(key: &[u8; 32]) -> LessSafeKey {
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).expect("AES-256 keys are 32 bytes"))
+}
+
+// AES-256-GCM with a random nonce; nonce || ciphertext || tag
+pub fn seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let mut in_out = plaintext.to_vec();
+    aes_key(key).seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(aad), &mut in_out)
+        .expect("plaintext fits in one AES-GCM message");
+    let mut sealed = nonce.to_vec();
+    sealed.extend(in_out);
+    sealed
+}
+
+pub fn open(key: &[u8; 32], sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    if sealed.len() < NONCE_LEN {
+        return Err(EnvelopeError::InvalidFormat);
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| EnvelopeError::InvalidFormat)?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = aes_key(key).open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| EnvelopeError::DecryptionFailed)?;
+    Ok(plaintext.to_vec())
+}
+
+// Splits "v1.<rest>" and checks the format version
+fn strip_version(s: &str) -> Result<&str, EnvelopeError> {
+    let (version, rest) = s.split_once('.').ok_or(EnvelopeError::InvalidFormat)?;
+    if version != FORMAT_VERSION {
+        return Err(EnvelopeError::UnsupportedVersion(version.to_string()));
+    }
+    Ok(rest)
+}
+
+pub struct DataKey([u8; 32]);
+
+impl DataKey {
+    pub fn new() -> DataKey {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        DataKey(key)
+    }
+
+    pub fn from_slice(key: &[u8]) -> Result<DataKey, EnvelopeError> {
+        Ok(DataKey(key.try_into().map_err(|_| EnvelopeError::InvalidKeyLength(key.len()))?))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    // `aad` names what is encrypted, e.g. the column, so ciphertexts cannot be swapped
+    pub fn encrypt(&self, plaintext: &[u8], aad: &str) -> String {
+        format!("{}.{}", FORMAT_VERSION, BASE64.encode(seal(&self.0, plaintext, aad.as_bytes())))
+    }
+
+    pub fn decrypt(&self, ciphertext: &str, aad: &str) -> Result<Vec<u8>, EnvelopeError> {
+        let sealed = BASE64.decode(strip_version(ciphertext)?).map_err(|_| EnvelopeError::InvalidFormat)?;
+        open(&self.0, &sealed, aad.as_bytes())
+    }
+}
+
+// A data key wrapped by version `version` of a master key.
+// The key manager holding the master key decides what `ciphertext` is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrappedKey {
+    pub version: u32,
+    pub ciphertext: Vec<u8>,
+}
+
+impl WrappedKey {
+    // What every wrapped key of master key `version` starts with, for queries
+    pub fn prefix(version: u32) -> String {
+        format!("{}.{}.", FORMAT_VERSION, version)
+    }
+}
+
+// v1.<master key version>.<base64 of ciphertext>
+impl fmt::Display for WrappedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", WrappedKey::prefix(self.version), BASE64.encode(&self.ciphertext))
+    }
+}
+
+impl FromStr for WrappedKey {
+    type Err = EnvelopeError;
+
+    fn from_str(s: &str) -> Result<WrappedKey, EnvelopeError> {
+        let (version, ciphertext) = strip_version(s)?.split_once('.').ok_or(EnvelopeError::InvalidFormat)?;
+        Ok(WrappedKey{
+            version: version.parse::<u32>().map_err(|_| EnvelopeError::InvalidFormat)?,
+            ciphertext: BASE64.decode(ciphertext).map_err(|_| EnvelopeError::InvalidFormat)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // Ciphertext made with the AESGCM of pyca/cryptography and a fixed nonce
+    fn test_decrypt() {
+        let data_key = DataKey::from_slice(&[0x42; 32]).unwrap();
+        let ciphertext = "v1.CwoJCAcGBQQDAgEAu6p7ma3Sasz4u7RNXc86IU1WVfb3mWmpymTi914tzlGH9XLozTu9aKm7QFTC/FqTQ617cDEsDM2VYknfjcN/LHVWZXY=";
+        assert_eq!(data_key.decrypt(ciphertext, "private_key").unwrap(), b"KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd");
+        assert_eq!(data_key.decrypt(ciphertext, "hd_seed").err(), Some(EnvelopeError::DecryptionFailed));
+        assert_eq!(data_key.decrypt(&ciphertext.replace("v1.", "v2."), "private_key").err(), Some(EnvelopeError::UnsupportedVersion("v2".to_string())));
+        assert_eq!(data_key.decrypt("v1.AAEC", "private_key").err(), Some(EnvelopeError::InvalidFormat));
+        assert_eq!(DataKey::from_slice(&[0x42; 16]).err(), Some(EnvelopeError::InvalidKeyLength(16)));
+    }
+
+    #[test]
+    fn test_wrapped_key() {
+        let wrapped = WrappedKey{ version: 2, ciphertext: vec![0, 1, 2] };
+        assert_eq!(wrapped.to_string(), "v1.2.AAEC");
+        assert!(wrapped.to_string().starts_with(&WrappedKey::prefix(2)));
+        assert_eq!("v1.2.AAEC".parse::<WrappedKey>().unwrap(), wrapped);
+        assert_eq!("v1.x.AAEC".parse::<WrappedKey>().err(), Some(EnvelopeError::InvalidFormat));
+        assert_eq!("v1.AAEC".parse::<WrappedKey>().err(), Some(EnvelopeError::InvalidFormat));
+    }
+}
@@ -2,30 +2,65 @@ use std::error::Error;
 use rand::rngs::OsRng;
 use secp256k1;
 use secp256k1::Secp256k1;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
 use sha3::{Digest, Keccak256};
-use crate::crypto::secret_key::bytes_32_to_wif;
-
-const ADDRESS_VERSION_BYTE_MAINNET: u8 = 0x00;
-const WIF_VERSION_BYTE_MAINNET: u8 = 0x80;
+use crate::crypto::secret_key::{bytes_32_to_wif, decode_compressed_wif, DEFAULT_VERSION_BYTE};
+use crate::crypto::eip712::hash_typed_data;
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
 
 pub struct EthereumKeypair {
+    pub secret_key: secp256k1::SecretKey,
     pub secret_key_compressed_wif: String,
     pub public_key: String,
     pub address: String,
 }
 
+// Last 20 bytes of the Keccak-256 of the uncompressed public key.
+// Tron addresses use the same bytes.
+pub fn keccak_address(public_key: &secp256k1::PublicKey) -> [u8; 20] {
+    let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+    hash[12..].try_into().expect("Keccak-256 is 32 bytes")
+}
+
+// r||s||v with v = 27 + recovery id
+pub fn sign_recoverable(secret_key: &secp256k1::SecretKey, digest: [u8; 32]) -> [u8; 65] {
+    let secp = Secp256k1::new();
+    let (recovery_id, signature) = secp
+        .sign_ecdsa_recoverable(&secp256k1::Message::from_digest(digest), secret_key)
+        .serialize_compact();
+    let mut result = [0u8; 65];
+    result[..64].copy_from_slice(&signature);
+    result[64] = 27 + recovery_id.to_i32() as u8;
+    result
+}
+
+// Whether `signature` is a sign_recoverable of `digest` by `secret_key`,
+// that is, whether the key recovered from it is the key's public key
+pub fn verify_recoverable(secret_key: &secp256k1::SecretKey, digest: [u8; 32], signature: &[u8]) -> bool {
+    let Ok(signature) = <&[u8; 65]>::try_from(signature) else {
+        return false;
+    };
+    let Ok(recovery_id) = RecoveryId::from_i32(signature[64] as i32 - 27) else {
+        return false;
+    };
+    let secp = Secp256k1::new();
+    RecoverableSignature::from_compact(&signature[..64], recovery_id)
+        .and_then(|signature| secp.recover_ecdsa(&secp256k1::Message::from_digest(digest), &signature))
+        .is_ok_and(|public_key| public_key == secret_key.public_key(&secp))
+}
+
 impl EthereumKeypair {
     pub fn new() -> EthereumKeypair {
         let secp = Secp256k1::new();
         let (secret_key, public_key) = secp.generate_keypair(&mut OsRng);
-        return Self::from_keypair(secret_key, public_key);
+        Self::from_keypair(secret_key, public_key)
     }
 
-    // checksum not checked!
     pub fn from_compressed_wif(wif: &str) -> Result<EthereumKeypair, Box<dyn Error>> {
-        let mut secret_key_bytes: [u8; 38] = [0x00; 38];
-        bs58::decode(wif).onto(&mut secret_key_bytes)?;
-        Ok(Self::from_secret_key_slice(&secret_key_bytes[1..33])?)
+        let secret_key_bytes = decode_compressed_wif(wif, &[DEFAULT_VERSION_BYTE])?;
+        Self::from_secret_key_slice(&secret_key_bytes)
     }
 
     pub fn from_secret_key_slice(s: &[u8]) -> Result<EthereumKeypair, Box<dyn Error>> {
@@ -45,7 +80,7 @@ impl EthereumKeypair {
         let mut result = String::new();
     
         for (i, c) in lower_raw_addr.chars().enumerate() {
-            if c >= 'a' && c <= 'f' && hash_str.chars().nth(i).unwrap().to_digit(16).unwrap() > 7 {
+            if ('a'..='f').contains(&c) && hash_str.chars().nth(i).unwrap().to_digit(16).unwrap() > 7 {
                 result.push(c.to_ascii_uppercase());
             } else {
                 result.push(c);
@@ -56,10 +91,7 @@ impl EthereumKeypair {
 
     pub fn from_keypair(secret_key: secp256k1::SecretKey, public_key: secp256k1::PublicKey) -> EthereumKeypair {
         let public_key_bytes = public_key.serialize();
-        let mut hasher = Keccak256::new();
-        hasher.update(&public_key.serialize_uncompressed()[1..]);
-        let public_key_hash = hasher.finalize();
-        let address = "0x".to_string() + &(public_key_hash[12..].iter()
+        let address = "0x".to_string() + &(keccak_address(&public_key).iter()
             .map(|b| format!("{:02X}", b))
             .collect::<Vec<String>>()
             .join("").to_owned());
@@ -71,9 +103,82 @@ impl EthereumKeypair {
             .join("");
 
         let secret_key_bytes = secret_key.secret_bytes().to_vec();
-        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, WIF_VERSION_BYTE_MAINNET);        
+        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, DEFAULT_VERSION_BYTE);        
+
+        EthereumKeypair{ secret_key, secret_key_compressed_wif, public_key: public_key_string, address: checksum_address}
+    }
+
+    // r||s||v with v = 27 + recovery id, as returned by eth_sign
+    pub fn sign_recoverable(&self, digest: [u8; 32]) -> [u8; 65] {
+        sign_recoverable(&self.secret_key, digest)
+    }
 
-        EthereumKeypair{ secret_key_compressed_wif: secret_key_compressed_wif, public_key: public_key_string, address: checksum_address}
+    // EIP-191 version 0x45: keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)
+    pub fn hash_personal_message(message: &[u8]) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+        hasher.update(message);
+        hasher.finalize().into()
+    }
+
+    // personal_sign
+    pub fn sign_personal_message(&self, message: &[u8]) -> [u8; 65] {
+        self.sign_recoverable(Self::hash_personal_message(message))
+    }
+
+    pub fn verify_personal_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        verify_recoverable(&self.secret_key, Self::hash_personal_message(message), signature)
+    }
+
+    // eth_signTypedData_v4
+    pub fn sign_typed_data(&self, typed_data: &serde_json::Value) -> Result<[u8; 65], Box<dyn Error>> {
+        Ok(self.sign_recoverable(hash_typed_data(typed_data)?))
+    }
+}
+
+// Ethereum addresses are the same on every network; chains differ by chain id
+impl ChainKeypair for EthereumKeypair {
+    fn generate(_network: Network) -> EthereumKeypair {
+        EthereumKeypair::new()
+    }
+
+    fn from_wif(wif: &str, _network: Network) -> Result<EthereumKeypair, Box<dyn Error>> {
+        EthereumKeypair::from_compressed_wif(wif)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], _network: Network) -> Result<EthereumKeypair, Box<dyn Error>> {
+        EthereumKeypair::from_secret_key_slice(s)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Secp256k1
+    }
+
+    // MetaMask numbers accounts by the address index
+    fn hd_path(account: u32, _network: Network) -> String {
+        format!("m/44'/60'/0'/0/{}", account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // 0x hex, as MetaMask imports
+    fn export_secret_key(&self) -> String {
+        format!("0x{}", hex::encode(self.secret_key.secret_bytes()))
+    }
+
+    // personal_sign
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_personal_message(message).to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_personal_message(message, signature)
     }
 }
 
@@ -87,4 +192,22 @@ mod tests {
         let keypair = EthereumKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
         assert_eq!(keypair.address, "0x5cf4e71E0d8466A958934Ce4e0D00b8ed1A3A973");
     }
-}
\ No newline at end of file
+
+    #[test]
+    // ref: https://web3js.readthedocs.io/en/v1.2.11/web3-eth-accounts.html#sign
+    fn test_sign_personal_message() {
+        let secret_key = hex::decode("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318").unwrap();
+        let keypair = EthereumKeypair::from_secret_key_slice(&secret_key).unwrap();
+        assert_eq!(hex::encode(EthereumKeypair::hash_personal_message(b"Some data")), "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655");
+        assert_eq!(hex::encode(keypair.sign_personal_message(b"Some data")), "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c");
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = EthereumKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&EthereumKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
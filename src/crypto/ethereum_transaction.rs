@@ -0,0 +1,234 @@
+// Signing of legacy (EIP-155) and EIP-1559 Ethereum transactions
+// ref: https://eips.ethereum.org/EIPS/eip-155
+// ref: https://eips.ethereum.org/EIPS/eip-1559
+use std::error::Error;
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use crate::crypto::eip712::encode_integer;
+use crate::crypto::ethereum_keypair::EthereumKeypair;
+use crate::crypto::rlp::{self, trim_leading_zeros, RlpItem};
+
+pub struct SignedTransaction {
+    pub raw_transaction: Vec<u8>,  // ready for eth_sendRawTransaction
+    pub hash: [u8; 32],
+}
+
+fn field<'a>(tx: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().filter_map(|k| tx.get(*k)).find(|v| !v.is_null())
+}
+
+// Unsigned integer from a JSON number or a decimal/0x-hex string
+fn quantity(tx: &Value, keys: &[&str]) -> Result<RlpItem, Box<dyn Error>> {
+    let value = field(tx, keys).ok_or(format!("No {}", keys[0]))?;
+    if value.as_str().is_some_and(|s| s.trim().starts_with('-')) || value.as_i64().is_some_and(|v| v < 0) {
+        return Err(format!("Negative {}", keys[0]).into());
+    }
+    Ok(RlpItem::Bytes(trim_leading_zeros(&encode_integer(value)?)))
+}
+
+fn optional_quantity(tx: &Value, keys: &[&str]) -> Result<RlpItem, Box<dyn Error>> {
+    match field(tx, keys) {
+        Some(_) => quantity(tx, keys),
+        None => Ok(0u64.into()),
+    }
+}
+
+fn hex_bytes(value: &Value, name: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    match value.as_str() {
+        Some(s) => Ok(hex::decode(s.trim_start_matches("0x"))?),
+        None => Err(format!("Invalid {}", name).into()),
+    }
+}
+
+fn fixed_hex_bytes(value: &Value, name: &str, len: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = hex_bytes(value, name)?;
+    if bytes.len() != len {
+        return Err(format!("{} must be {} bytes", name, len).into());
+    }
+    Ok(bytes)
+}
+
+fn chain_id(tx: &Value, default_chain_id: u64) -> Result<u64, Box<dyn Error>> {
+    let word = match field(tx, &["chainId"]) {
+        Some(v) => encode_integer(v)?,
+        None => return Ok(default_chain_id),
+    };
+    if word[..24].iter().any(|b| *b != 0) {
+        return Err("chainId too large".into());
+    }
+    Ok(u64::from_be_bytes(word[24..].try_into()?))
+}
+
+// Empty for contract creation
+fn to(tx: &Value) -> Result<RlpItem, Box<dyn Error>> {
+    let to = match field(tx, &["to"]) {
+        Some(v) => hex_bytes(v, "to")?,
+        None => vec![],
+    };
+    if !to.is_empty() && to.len() != 20 {
+        return Err("Invalid to".into());
+    }
+    Ok(RlpItem::Bytes(to))
+}
+
+fn data(tx: &Value) -> Result<RlpItem, Box<dyn Error>> {
+    match field(tx, &["data", "input"]) {
+        Some(v) => Ok(RlpItem::Bytes(hex_bytes(v, "data")?)),
+        None => Ok(RlpItem::Bytes(vec![])),
+    }
+}
+
+// [{"address": "0x...", "storageKeys": ["0x..."]}] -> [[address, [key, ...]], ...]
+fn access_list(tx: &Value) -> Result<RlpItem, Box<dyn Error>> {
+    let mut items = vec![];
+    if let Some(list) = field(tx, &["accessList"]) {
+        for entry in list.as_array().ok_or("Invalid accessList")? {
+            let address = fixed_hex_bytes(entry.get("address").unwrap_or(&Value::Null), "accessList address", 20)?;
+            let mut storage_keys = vec![];
+            if let Some(keys) = entry.get("storageKeys") {
+                for key in keys.as_array().ok_or("Invalid storageKeys")? {
+                    storage_keys.push(RlpItem::Bytes(fixed_hex_bytes(key, "storage key", 32)?));
+                }
+            }
+            items.push(RlpItem::List(vec![RlpItem::Bytes(address), RlpItem::List(storage_keys)]));
+        }
+    }
+    Ok(RlpItem::List(items))
+}
+
+fn is_eip1559(tx: &Value) -> Result<bool, Box<dyn Error>> {
+    match field(tx, &["type"]) {
+        Some(t) => match encode_integer(t)? {
+            word if word[..31] != [0; 31] => Err(format!("Unsupported transaction type {}", t).into()),
+            word => match word[31] {
+                0 => Ok(false),
+                2 => Ok(true),
+                _ => Err(format!("Unsupported transaction type {}", t).into()),
+            },
+        },
+        None => Ok(field(tx, &["maxFeePerGas"]).is_some()),
+    }
+}
+
+fn split_signature(signature: &[u8; 65]) -> (RlpItem, RlpItem, u64) {
+    let r = RlpItem::Bytes(trim_leading_zeros(&signature[..32]));
+    let s = RlpItem::Bytes(trim_leading_zeros(&signature[32..64]));
+    let recovery_id = (signature[64] - 27) as u64;
+    (r, s, recovery_id)
+}
+
+fn sign_legacy(keypair: &EthereumKeypair, tx: &Value, default_chain_id: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let chain_id = chain_id(tx, default_chain_id)?;
+    let fields = vec![
+        quantity(tx, &["nonce"])?,
+        quantity(tx, &["gasPrice"])?,
+        quantity(tx, &["gas", "gasLimit"])?,
+        to(tx)?,
+        optional_quantity(tx, &["value"])?,
+        data(tx)?,
+    ];
+    // EIP-155 replay protection: sign [..., chainId, 0, 0]
+    let mut unsigned = fields.clone();
+    unsigned.extend([chain_id.into(), 0u64.into(), 0u64.into()]);
+    let sighash: [u8; 32] = Keccak256::digest(rlp::encode(&RlpItem::List(unsigned))).into();
+    let (r, s, recovery_id) = split_signature(&keypair.sign_recoverable(sighash));
+    let v = chain_id.checked_mul(2)
+        .and_then(|v| v.checked_add(35 + recovery_id))
+        .ok_or("chainId too large for EIP-155")?;
+    let mut signed = fields;
+    signed.extend([v.into(), r, s]);
+    Ok(rlp::encode(&RlpItem::List(signed)))
+}
+
+fn sign_eip1559(keypair: &EthereumKeypair, tx: &Value, default_chain_id: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let fields = vec![
+        chain_id(tx, default_chain_id)?.into(),
+        quantity(tx, &["nonce"])?,
+        quantity(tx, &["maxPriorityFeePerGas"])?,
+        quantity(tx, &["maxFeePerGas"])?,
+        quantity(tx, &["gas", "gasLimit"])?,
+        to(tx)?,
+        optional_quantity(tx, &["value"])?,
+        data(tx)?,
+        access_list(tx)?,
+    ];
+    // 0x02 || rlp([chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gas, to, value, data, accessList])
+    let mut unsigned = vec![0x02];
+    unsigned.extend(rlp::encode(&RlpItem::List(fields.clone())));
+    let sighash: [u8; 32] = Keccak256::digest(&unsigned).into();
+    let (r, s, y_parity) = split_signature(&keypair.sign_recoverable(sighash));
+    let mut signed = fields;
+    signed.extend([y_parity.into(), r, s]);
+    let mut raw_transaction = vec![0x02];
+    raw_transaction.extend(rlp::encode(&RlpItem::List(signed)));
+    Ok(raw_transaction)
+}
+
+// {"nonce": 9, "gasPrice": "20000000000", "gas": 21000, "to": "0x...", "value": "0xde0b6b3a7640000", "data": "0x", "chainId": 1}
+// EIP-1559 when "type" is 2 or "maxFeePerGas" is given; legacy otherwise.
+// `default_chain_id` applies when the transaction has no chainId.
+pub fn sign_transaction(keypair: &EthereumKeypair, tx: &Value, default_chain_id: u64) -> Result<SignedTransaction, Box<dyn Error>> {
+    let raw_transaction = if is_eip1559(tx)? {
+        sign_eip1559(keypair, tx, default_chain_id)?
+    } else {
+        sign_legacy(keypair, tx, default_chain_id)?
+    };
+    let hash = Keccak256::digest(&raw_transaction).into();
+    Ok(SignedTransaction{ raw_transaction, hash })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // ref: https://eips.ethereum.org/EIPS/eip-155#example
+    fn test_sign_legacy() {
+        let keypair = EthereumKeypair::from_secret_key_slice(&[0x46; 32]).unwrap();
+        let tx: Value = serde_json::from_str(r#"{
+            "nonce": 9,
+            "gasPrice": "20000000000",
+            "gas": 21000,
+            "to": "0x3535353535353535353535353535353535353535",
+            "value": "1000000000000000000",
+            "data": "0x",
+            "chainId": 1
+        }"#).unwrap();
+        let signed = sign_transaction(&keypair, &tx, 5).unwrap();
+        assert_eq!(hex::encode(signed.raw_transaction), "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+    }
+
+    #[test]
+    // Checked against an independent Python implementation (Keccak-256, RLP,
+    // RFC 6979) that reproduces the EIP-155 example above
+    fn test_sign_eip1559() {
+        let keypair = EthereumKeypair::from_secret_key_slice(&[0x46; 32]).unwrap();
+        let tx: Value = serde_json::from_str(r#"{
+            "chainId": "0x1",
+            "nonce": "0x0",
+            "maxPriorityFeePerGas": "1000000000",
+            "maxFeePerGas": "30000000000",
+            "gas": 21000,
+            "to": "0x3535353535353535353535353535353535353535",
+            "value": "0xde0b6b3a7640000",
+            "accessList": [{"address": "0x3535353535353535353535353535353535353535", "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000001"]}]
+        }"#).unwrap();
+        let signed = sign_transaction(&keypair, &tx, 5).unwrap();
+        assert_eq!(hex::encode(signed.raw_transaction), "02f8ac0180843b9aca008506fc23ac00825208943535353535353535353535353535353535353535880de0b6b3a764000080f838f7943535353535353535353535353535353535353535e1a0000000000000000000000000000000000000000000000000000000000000000101a0da339482e167d7b9dab7b724aee5ec91d90daa818e2ab1fc3f09fc6c1d105434a071adec8b7a48d8460c638cd1f99c6e97aa84188776b8b66c8c61973bca225d8a");
+        assert_eq!(hex::encode(signed.hash), "95fc7b8631dc627b08147e76327c3193a2b2976dbc819f732dcb089bf7bebfba");
+    }
+
+    #[test]
+    fn test_invalid_transaction() {
+        let keypair = EthereumKeypair::from_secret_key_slice(&[0x46; 32]).unwrap();
+        let legacy = r#"{"nonce": 9, "gasPrice": "20000000000", "gas": 21000, "to": "0x3535353535353535353535353535353535353535", "chainId": "0xffffffffffffffff"}"#;
+        assert!(sign_transaction(&keypair, &serde_json::from_str(legacy).unwrap(), 1).is_err());
+        let short_address = r#"{"type": 2, "nonce": 0, "maxPriorityFeePerGas": 1, "maxFeePerGas": 1, "gas": 21000, "accessList": [{"address": "0x3535", "storageKeys": []}]}"#;
+        assert!(sign_transaction(&keypair, &serde_json::from_str(short_address).unwrap(), 1).is_err());
+        let short_key = r#"{"type": 2, "nonce": 0, "maxPriorityFeePerGas": 1, "maxFeePerGas": 1, "gas": 21000, "accessList": [{"address": "0x3535353535353535353535353535353535353535", "storageKeys": ["0x01"]}]}"#;
+        assert!(sign_transaction(&keypair, &serde_json::from_str(short_key).unwrap(), 1).is_err());
+        // 0x102 ends in 0x02 but is not type 2
+        let wide_type = r#"{"type": "0x102", "nonce": 0, "maxPriorityFeePerGas": 1, "maxFeePerGas": 1, "gas": 21000}"#;
+        assert!(sign_transaction(&keypair, &serde_json::from_str(wide_type).unwrap(), 1).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+// Hierarchical deterministic keys: BIP-32 for secp256k1, and its
+// generalization SLIP-10 for NIST P-256 (Neo) and ed25519 (hardened only).
+// ref: https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki
+// ref: https://github.com/satoshilabs/slips/blob/master/slip-0010.md
+use std::error::Error;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use p256::elliptic_curve::{ff::Field, PrimeField};
+use secp256k1::Secp256k1;
+
+const HARDENED: u32 = 0x80000000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Secp256k1,
+    Nist256p1,
+    Ed25519,
+}
+
+impl Curve {
+    fn master_key(&self) -> &'static [u8] {
+        match self {
+            Curve::Secp256k1 => b"Bitcoin seed",
+            Curve::Nist256p1 => b"Nist256p1 seed",
+            Curve::Ed25519 => b"ed25519 seed",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ExtendedPrivateKey {
+    pub curve: Curve,
+    pub secret_key: [u8; 32],
+    pub chain_code: [u8; 32],
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    let i = mac.finalize().into_bytes();
+    (i[..32].try_into().unwrap(), i[32..].try_into().unwrap())
+}
+
+// `None` when IL is not a valid key, and SLIP-10 derives again
+fn add_scalars(curve: Curve, il: &[u8; 32], parent: &[u8; 32]) -> Option<[u8; 32]> {
+    match curve {
+        Curve::Secp256k1 => {
+            let tweak = secp256k1::Scalar::from_be_bytes(*il).ok()?;
+            let parent = secp256k1::SecretKey::from_slice(parent).ok()?;
+            Some(parent.add_tweak(&tweak).ok()?.secret_bytes())
+        },
+        Curve::Nist256p1 => {
+            let tweak: Option<p256::Scalar> = p256::Scalar::from_repr((*il).into()).into();
+            let parent: Option<p256::Scalar> = p256::Scalar::from_repr((*parent).into()).into();
+            let child = tweak? + parent?;
+            if bool::from(child.is_zero()) {
+                return None;
+            }
+            Some(child.to_repr().into())
+        },
+        Curve::Ed25519 => Some(*il),
+    }
+}
+
+fn is_valid_key(curve: Curve, key: &[u8; 32]) -> bool {
+    match curve {
+        Curve::Secp256k1 => secp256k1::SecretKey::from_slice(key).is_ok(),
+        Curve::Nist256p1 => p256::ecdsa::SigningKey::from_slice(key).is_ok(),
+        Curve::Ed25519 => true,
+    }
+}
+
+impl ExtendedPrivateKey {
+    pub fn from_seed(seed: &[u8], curve: Curve) -> ExtendedPrivateKey {
+        let mut data = seed.to_vec();
+        loop {
+            let (secret_key, chain_code) = hmac_sha512(curve.master_key(), &data);
+            if is_valid_key(curve, &secret_key) {
+                return ExtendedPrivateKey{ curve, secret_key, chain_code };
+            }
+            data = [secret_key, chain_code].concat();
+        }
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        match self.curve {
+            Curve::Secp256k1 => {
+                let secret_key = secp256k1::SecretKey::from_slice(&self.secret_key).expect("valid by construction");
+                secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &secret_key).serialize().to_vec()
+            },
+            Curve::Nist256p1 => {
+                let secret_key = p256::ecdsa::SigningKey::from_slice(&self.secret_key).expect("valid by construction");
+                secret_key.verifying_key().to_encoded_point(true).as_bytes().to_vec()
+            },
+            Curve::Ed25519 => unreachable!("ed25519 has hardened derivation only"),
+        }
+    }
+
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedPrivateKey, Box<dyn Error>> {
+        let mut data = if index >= HARDENED {
+            let mut data = vec![0x00];
+            data.extend(self.secret_key);
+            data
+        } else if self.curve == Curve::Ed25519 {
+            return Err("ed25519 keys can only be derived at hardened indexes".into());
+        } else {
+            self.public_key()
+        };
+        data.extend(index.to_be_bytes());
+        loop {
+            let (il, chain_code) = hmac_sha512(&self.chain_code, &data);
+            if let Some(secret_key) = add_scalars(self.curve, &il, &self.secret_key) {
+                return Ok(ExtendedPrivateKey{ curve: self.curve, secret_key, chain_code });
+            }
+            data = vec![0x01];
+            data.extend(chain_code);
+            data.extend(index.to_be_bytes());
+        }
+    }
+
+    // e.g. "m/44'/60'/0'/0/0"; h and H also mark hardened indexes
+    pub fn derive_path(&self, path: &str) -> Result<ExtendedPrivateKey, Box<dyn Error>> {
+        let mut segments = path.split('/');
+        if segments.next() != Some("m") {
+            return Err(format!("Derivation path {} must start with m", path).into());
+        }
+        let mut key = self.clone();
+        for segment in segments {
+            let (number, hardened) = match segment.strip_suffix(['\'', 'h', 'H']) {
+                Some(number) => (number, true),
+                None => (segment, false),
+            };
+            let index: u32 = number.parse().map_err(|_| format!("Invalid index {} in derivation path", segment))?;
+            if index >= HARDENED {
+                return Err(format!("Index {} out of range in derivation path", segment).into());
+            }
+            key = key.derive_child(if hardened { index + HARDENED } else { index })?;
+        }
+        Ok(key)
+    }
+}
+
+pub fn derive_secret_key(seed: &[u8], curve: Curve, path: &str) -> Result<[u8; 32], Box<dyn Error>> {
+    Ok(ExtendedPrivateKey::from_seed(seed, curve).derive_path(path)?.secret_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vector 1 of BIP-32 and SLIP-10
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    #[test]
+    fn test_secp256k1() {
+        let seed = hex::decode(SEED).unwrap();
+        let master = ExtendedPrivateKey::from_seed(&seed, Curve::Secp256k1);
+        assert_eq!(hex::encode(master.secret_key), "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35");
+        assert_eq!(hex::encode(master.chain_code), "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508");
+        let key = derive_secret_key(&seed, Curve::Secp256k1, "m/0'/1/2'/2/1000000000").unwrap();
+        assert_eq!(hex::encode(key), "471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8");
+    }
+
+    #[test]
+    fn test_nist256p1() {
+        let seed = hex::decode(SEED).unwrap();
+        let key = derive_secret_key(&seed, Curve::Nist256p1, "m").unwrap();
+        assert_eq!(hex::encode(key), "612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2");
+        let key = derive_secret_key(&seed, Curve::Nist256p1, "m/0'/1/2'/2/1000000000").unwrap();
+        assert_eq!(hex::encode(key), "21c4f269ef0a5fd1badf47eeacebeeaa3de22eb8e5b0adcd0f27dd99d34d0119");
+    }
+
+    #[test]
+    fn test_ed25519() {
+        let seed = hex::decode(SEED).unwrap();
+        let key = derive_secret_key(&seed, Curve::Ed25519, "m").unwrap();
+        assert_eq!(hex::encode(key), "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7");
+        let key = derive_secret_key(&seed, Curve::Ed25519, "m/0H/1H/2H/2H/1000000000H").unwrap();
+        assert_eq!(hex::encode(key), "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793");
+        assert!(derive_secret_key(&seed, Curve::Ed25519, "m/0'/1").is_err());
+    }
+
+    #[test]
+    fn test_invalid_path() {
+        let seed = hex::decode(SEED).unwrap();
+        assert!(derive_secret_key(&seed, Curve::Secp256k1, "44'/0'").is_err());
+        assert!(derive_secret_key(&seed, Curve::Secp256k1, "m/x").is_err());
+        assert!(derive_secret_key(&seed, Curve::Secp256k1, "m/2147483648").is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use rand::rngs::OsRng;
+use secp256k1;
+use secp256k1::Secp256k1;
+use crate::crypto::bech32::encode_segwit_address;
+use crate::crypto::bitcoin_keypair::{base58check, hash160, sign_message_with_magic, verify_message_with_magic};
+use crate::crypto::secret_key::{bytes_32_to_wif, decode_compressed_wif, DEFAULT_VERSION_BYTE};
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
+
+// P2PKH version, P2SH version, WIF version, bech32 HRP
+fn versions(network: Network) -> (u8, u8, u8, &'static str) {
+    match network {
+        Network::Mainnet => (0x30, 0x32, 0xb0, "ltc"),
+        Network::Testnet => (0x6f, 0x3a, 0xef, "tltc"),
+        Network::Regtest => (0x6f, 0x3a, 0xef, "rltc"),
+    }
+}
+
+const MESSAGE_MAGIC: &str = "Litecoin Signed Message:\n";
+
+pub struct LitecoinKeypair {
+    pub secret_key: secp256k1::SecretKey,
+    pub secret_key_compressed_wif: String,
+    pub public_key: String,
+    pub address: String,  // legacy P2PKH, L...
+    pub segwit_address: String,  // P2WPKH, ltc1q...
+    pub nested_segwit_address: String,  // P2SH-P2WPKH, M...
+}
+
+impl LitecoinKeypair {
+    pub fn new(network: Network) -> LitecoinKeypair {
+        let secp = Secp256k1::new();
+        let (secret_key, public_key) = secp.generate_keypair(&mut OsRng);
+        LitecoinKeypair::from_keypair(secret_key, public_key, network)
+    }
+
+    // Accounts are stored with the Bitcoin mainnet version byte
+    pub fn from_compressed_wif(wif: &str, network: Network) -> Result<LitecoinKeypair, Box<dyn Error>> {
+        let secret_key_bytes = decode_compressed_wif(wif, &[versions(network).2, DEFAULT_VERSION_BYTE])?;
+        LitecoinKeypair::from_secret_key_slice(&secret_key_bytes, network)
+    }
+
+    pub fn from_secret_key_slice(s: &[u8], network: Network) -> Result<LitecoinKeypair, Box<dyn Error>> {
+        let secp = Secp256k1::new();
+        let sk = secp256k1::SecretKey::from_slice(s)?;
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &sk);
+        Ok(LitecoinKeypair::from_keypair(sk, public_key, network))
+    }
+
+    pub fn from_keypair(secret_key: secp256k1::SecretKey, public_key: secp256k1::PublicKey, network: Network) -> LitecoinKeypair {
+        let (p2pkh_version, p2sh_version, wif_version, hrp) = versions(network);
+        let public_key_bytes = public_key.serialize();
+        let pubkey_hash = hash160(&public_key_bytes);
+
+        let mut payload = vec![p2pkh_version];
+        payload.extend(pubkey_hash);
+        let address = base58check(payload);
+
+        let segwit_address = encode_segwit_address(hrp, 0, &pubkey_hash);
+
+        let mut redeem_script = vec![0x00, 0x14];  // OP_0 PUSH20
+        redeem_script.extend(pubkey_hash);
+        let mut payload = vec![p2sh_version];
+        payload.extend(hash160(&redeem_script));
+        let nested_segwit_address = base58check(payload);
+
+        let public_key_string: String = public_key_bytes.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join("");
+
+        let secret_key_bytes = secret_key.secret_bytes().to_vec();
+        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, wif_version);
+
+        LitecoinKeypair{ secret_key, secret_key_compressed_wif, public_key: public_key_string, address, segwit_address, nested_segwit_address }
+    }
+
+    pub fn sign_message(&self, message: &[u8]) -> [u8; 65] {
+        sign_message_with_magic(&self.secret_key, MESSAGE_MAGIC, message)
+    }
+
+    pub fn verify_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        verify_message_with_magic(&self.secret_key, MESSAGE_MAGIC, message, signature)
+    }
+}
+
+impl ChainKeypair for LitecoinKeypair {
+    fn generate(network: Network) -> LitecoinKeypair {
+        LitecoinKeypair::new(network)
+    }
+
+    fn from_wif(wif: &str, network: Network) -> Result<LitecoinKeypair, Box<dyn Error>> {
+        LitecoinKeypair::from_compressed_wif(wif, network)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], network: Network) -> Result<LitecoinKeypair, Box<dyn Error>> {
+        LitecoinKeypair::from_secret_key_slice(s, network)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Secp256k1
+    }
+
+    fn hd_path(account: u32, network: Network) -> String {
+        let coin_type = if network == Network::Mainnet { 2 } else { 1 };
+        format!("m/44'/{}'/{}'/0/0", coin_type, account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // WIF, as Litecoin Core importprivkey
+    fn export_secret_key(&self) -> String {
+        self.secret_key_compressed_wif.clone()
+    }
+
+    fn addresses(&self) -> BTreeMap<&'static str, String> {
+        BTreeMap::from([
+            ("p2pkh", self.address.to_owned()),
+            ("p2wpkh", self.segwit_address.to_owned()),
+            ("p2sh-p2wpkh", self.nested_segwit_address.to_owned()),
+        ])
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_message(message).to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_message(message, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+    #[test]
+    fn test_addr() {
+        let keypair = LitecoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", Network::Mainnet).unwrap();
+        assert_eq!(keypair.address, "LTVHDgUVuUEvPEE4vvtrTDE68XJKa2PNWE");
+        assert_eq!(keypair.segwit_address, "ltc1qt2n3r4txfdy3f8hyfcnwjmxmhwa4u0jhnzxv0c");
+        assert_eq!(keypair.nested_segwit_address, "MPjWCKykXhp1TsgsFNjxNL4CmC6JGQMyX9");
+        assert_eq!(keypair.secret_key_compressed_wif, "T5B29CNVUNKry5sitXqpYnceKEYHLK9v7cuvQR9dQiFZeUMSckjR");
+        // Litecoin's own WIF gives back the same key
+        let keypair = LitecoinKeypair::from_compressed_wif(&keypair.secret_key_compressed_wif, Network::Mainnet).unwrap();
+        assert_eq!(keypair.address, "LTVHDgUVuUEvPEE4vvtrTDE68XJKa2PNWE");
+    }
+
+    #[test]
+    fn test_testnet_addr() {
+        let keypair = LitecoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", Network::Testnet).unwrap();
+        assert_eq!(keypair.address, "monHFXFedqS7uY1XUMsw17NenJXkKvyKjL");
+        assert_eq!(keypair.segwit_address, "tltc1qt2n3r4txfdy3f8hyfcnwjmxmhwa4u0jhys99uj");
+    }
+
+    #[test]
+    // Checked against an independent Python implementation of signmessage
+    fn test_sign_message() {
+        let keypair = LitecoinKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", Network::Mainnet).unwrap();
+        assert_eq!(BASE64.encode(keypair.sign_message(b"hello")), "HxrSMgttI7oPjCEe+odlHxXnH1qxW1rwXAhfd2v0YiabMqp8OXfiUS8bC5ce0J1pGnDqXvMAdSbTvSgsmrF/3YE=");
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = LitecoinKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&LitecoinKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
@@ -1,5 +1,22 @@
+pub mod aptos_keypair;
+pub mod bech32;
+pub mod bip39;
 pub mod bitcoin_keypair;
+pub mod bitcoin_psbt;
+pub mod chain_keypair;
+pub mod cosmos_keypair;
+pub mod dogecoin_keypair;
 pub mod ethereum_keypair;
+pub mod eip712;
+pub mod envelope;
+pub mod ethereum_transaction;
+pub mod hd;
+pub mod litecoin_keypair;
 pub mod neo_keypair;
+pub mod neo_transaction;
+pub mod network;
+pub mod rlp;
 pub mod secret_key;
-// TODO: ed25519, Solana, Aptos, Sui
\ No newline at end of file
+pub mod solana_keypair;
+pub mod sui_keypair;
+pub mod tron_keypair;
\ No newline at end of file
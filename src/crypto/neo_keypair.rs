@@ -1,13 +1,20 @@
 use std::error::Error;
 use rand::rngs::OsRng;
-use p256::ecdsa::{SigningKey, VerifyingKey};
+use p256::ecdsa::{signature::{Signer, Verifier}, Signature, SigningKey, VerifyingKey};
 use secp256k1::hashes::{sha256::Hash as Sha256Hash, ripemd160::Hash as Ripemp160Hash, Hash};
-use crate::crypto::secret_key::bytes_32_to_wif;
+use crate::crypto::secret_key::{bytes_32_to_wif, decode_compressed_wif};
+use crate::crypto::neo_transaction::{write_var_bytes, NeoTransaction, Witness};
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
 
-const ADDRESS_VERSION_BYTE_MAINNET: u8 = 53;
-const WIF_VERSION_BYTE_MAINNET: u8 = 0x80;
+// Neo N3 uses the same versions on MainNet and TestNet;
+// networks only differ in the magic of the signed data
+const ADDRESS_VERSION_BYTE: u8 = 53;
+const WIF_VERSION_BYTE: u8 = 0x80;
 
 pub struct NeoKeypair {
+    pub secret_key: SigningKey,
     pub secret_key_compressed_wif: String,
     pub public_key: String,
     pub address: String,
@@ -17,31 +24,45 @@ impl NeoKeypair {
     pub fn new() -> NeoKeypair {
         let secret_key = SigningKey::random(&mut OsRng);
         let public_key = VerifyingKey::from(&secret_key);
-        return NeoKeypair::from_keypair(secret_key, public_key);
+        NeoKeypair::from_keypair(secret_key, public_key)
     }
 
-    // checksum not checked!
     pub fn from_compressed_wif(wif: &str) -> Result<NeoKeypair, Box<dyn Error>> {
-        let mut secret_key_bytes: [u8; 38] = [0x00; 38];
-        bs58::decode(wif).onto(&mut secret_key_bytes)?;
-        Ok(NeoKeypair::from_secret_key_slice(&secret_key_bytes[1..33])?)
+        let secret_key_bytes = decode_compressed_wif(wif, &[WIF_VERSION_BYTE])?;
+        NeoKeypair::from_secret_key_slice(&secret_key_bytes)
     }
 
     pub fn from_secret_key_slice(s: &[u8]) -> Result<NeoKeypair, Box<dyn Error>> {
-        let secret_key = SigningKey::from_slice(s).unwrap();
+        let secret_key = SigningKey::from_slice(s)?;
         let public_key = VerifyingKey::from(&secret_key);
         Ok(NeoKeypair::from_keypair(secret_key, public_key))
     }
 
-    pub fn from_keypair(secret_key: p256::ecdsa::SigningKey, public_key: p256::ecdsa::VerifyingKey) -> NeoKeypair {
-        let public_key_bytes = public_key.to_encoded_point(true).to_bytes();
+    pub fn verification_script_of(public_key_bytes: &[u8]) -> Vec<u8> {
         let mut verification_script: Vec<u8> = Vec::new();
         verification_script.extend_from_slice(&[0x0c, 0x21]);  // PUSHDATA1
-        verification_script.extend_from_slice(&public_key_bytes);
+        verification_script.extend_from_slice(public_key_bytes);
         verification_script.extend_from_slice(&[0x41, 0x56, 0xe7, 0xb3, 0x27]);  // SYSCALL System.Crypto.CheckSig
+        verification_script
+    }
+
+    pub fn verification_script(&self) -> Vec<u8> {
+        let public_key_bytes = VerifyingKey::from(&self.secret_key).to_encoded_point(true).to_bytes();
+        Self::verification_script_of(&public_key_bytes)
+    }
+
+    // The account's UInt160, little-endian as serialized in transactions
+    pub fn script_hash(&self) -> [u8; 20] {
+        let script_sha256 = Sha256Hash::hash(&self.verification_script()).to_byte_array();
+        Ripemp160Hash::hash(&script_sha256).to_byte_array()
+    }
+
+    pub fn from_keypair(secret_key: p256::ecdsa::SigningKey, public_key: p256::ecdsa::VerifyingKey) -> NeoKeypair {
+        let public_key_bytes = public_key.to_encoded_point(true).to_bytes();
+        let verification_script = Self::verification_script_of(&public_key_bytes);
         let script_sha256 = Sha256Hash::hash(&verification_script).to_byte_array();
         let mut script_hash = Ripemp160Hash::hash(&script_sha256).to_byte_array().to_vec();
-        script_hash.insert(0, ADDRESS_VERSION_BYTE_MAINNET);
+        script_hash.insert(0, ADDRESS_VERSION_BYTE);
         let checksum = &Sha256Hash::hash(
             &Sha256Hash::hash(&script_hash)
                 .to_byte_array()
@@ -55,15 +76,112 @@ impl NeoKeypair {
             .join("");
 
         let secret_key_bytes = secret_key.to_bytes().to_vec();
-        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, WIF_VERSION_BYTE_MAINNET);        
+        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, WIF_VERSION_BYTE);        
+
+        NeoKeypair{ secret_key, secret_key_compressed_wif, public_key: public_key_string, address}
+    }
+
+    // ECDSA (P-256) over the SHA-256 of the message; r||s, as Neo expects in invocation scripts
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        let signature: Signature = self.secret_key.sign(message);
+        signature.to_bytes().into()
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return false;
+        };
+        VerifyingKey::from(&self.secret_key).verify(message, &signature).is_ok()
+    }
 
-        NeoKeypair{ secret_key_compressed_wif: secret_key_compressed_wif, public_key: public_key_string, address: address}
+    // signMessageWithoutSalt of NeoLine and Neon:
+    // 010001f0 || var int length || message || 0000, so it never parses as a transaction
+    pub fn message_data(message: &[u8]) -> Vec<u8> {
+        let mut data = vec![0x01, 0x00, 0x01, 0xf0];
+        write_var_bytes(&mut data, message);
+        data.extend([0x00, 0x00]);
+        data
+    }
+
+    pub fn sign_message(&self, message: &[u8]) -> [u8; 64] {
+        self.sign(&Self::message_data(message))
+    }
+
+    pub fn verify_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify(&Self::message_data(message), signature)
+    }
+
+    // Invocation script PUSHDATA1 <signature of the sign data> with the matching verification script
+    pub fn sign_witness(&self, tx: &NeoTransaction, network: u32) -> Witness {
+        let mut invocation_script = vec![0x0c, 0x40];  // PUSHDATA1 64 bytes
+        invocation_script.extend(self.sign(&tx.sign_data(network)));
+        Witness{ invocation_script, verification_script: self.verification_script() }
+    }
+
+    // Attach this account's witness at the position of its signer.
+    // Witnesses of other signers must be given in the transaction already.
+    pub fn sign_transaction(&self, tx: &mut NeoTransaction, network: u32) -> Result<(), Box<dyn Error>> {
+        let script_hash = self.script_hash();
+        let index = tx.signers.iter().position(|s| s.account == script_hash).ok_or("Not a signer of the transaction")?;
+        let witness = self.sign_witness(tx, network);
+        while tx.witnesses.len() < tx.signers.len() {
+            tx.witnesses.push(Witness{ invocation_script: vec![], verification_script: vec![] });
+        }
+        tx.witnesses[index] = witness;
+        if tx.witnesses.iter().any(|w| w.verification_script.is_empty()) {
+            return Err("Missing witnesses of other signers".into());
+        }
+        Ok(())
+    }
+}
+
+impl ChainKeypair for NeoKeypair {
+    fn generate(_network: Network) -> NeoKeypair {
+        NeoKeypair::new()
+    }
+
+    fn from_wif(wif: &str, _network: Network) -> Result<NeoKeypair, Box<dyn Error>> {
+        NeoKeypair::from_compressed_wif(wif)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], _network: Network) -> Result<NeoKeypair, Box<dyn Error>> {
+        NeoKeypair::from_secret_key_slice(s)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Nist256p1
+    }
+
+    fn hd_path(account: u32, _network: Network) -> String {
+        format!("m/44'/888'/0'/0/{}", account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // WIF, as Neon and NeoLine import
+    fn export_secret_key(&self) -> String {
+        self.secret_key_compressed_wif.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_message(message).to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_message(message, signature)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use p256::ecdsa::signature::Verifier;
 
     #[test]
     // ref: https://neo.org/converter/index
@@ -71,4 +189,45 @@ mod tests {
         let keypair = NeoKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
         assert_eq!(keypair.address, "NUrR2m4hRyYTdzFseDZzUKLhj5YLJtjEN2");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sign() {
+        let keypair = NeoKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        let signature = Signature::from_slice(&keypair.sign(b"hello")).unwrap();
+        assert!(VerifyingKey::from(&keypair.secret_key).verify(b"hello", &signature).is_ok());
+    }
+
+    #[test]
+    // Checked against the deterministic ECDSA of OpenSSL
+    fn test_sign_message() {
+        let keypair = NeoKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        assert_eq!(hex::encode(keypair.sign_message(b"hello")), "6149ad9c3bb13aac52849d209f512bcb59a2a366ca7c92a80743fc9622140d76d9576e3fd97567afff783421c3ad07f4f079520869046d2782a9801c4f1783ee");
+        let signature = Signature::from_slice(&keypair.sign_message(b"hello")).unwrap();
+        assert!(VerifyingKey::from(&keypair.secret_key).verify(&hex::decode("010001f00568656c6c6f0000").unwrap(), &signature).is_ok());
+    }
+
+    #[test]
+    fn test_sign_transaction() {
+        use crate::crypto::neo_transaction::NETWORK_MAGIC_MAINNET;
+        let keypair = NeoKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        let tx: serde_json::Value = serde_json::from_str(r#"{"nonce": 1, "sysfee": 0, "netfee": 0, "validuntilblock": 100, "script": "EUA="}"#).unwrap();
+        let mut tx = NeoTransaction::from_json(&tx, keypair.script_hash()).unwrap();
+        keypair.sign_transaction(&mut tx, NETWORK_MAGIC_MAINNET).unwrap();
+        let witness = &tx.witnesses[0];
+        assert_eq!(witness.invocation_script[..2], [0x0c, 0x40]);
+        let signature = Signature::from_slice(&witness.invocation_script[2..]).unwrap();
+        let public_key = VerifyingKey::from(&keypair.secret_key);
+        assert!(public_key.verify(&tx.sign_data(NETWORK_MAGIC_MAINNET), &signature).is_ok());
+        // Decoding the address must give back the script hash
+        assert_eq!(crate::crypto::neo_transaction::parse_script_hash(&keypair.address).unwrap(), keypair.script_hash());
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = NeoKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&NeoKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
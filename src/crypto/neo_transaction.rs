@@ -0,0 +1,351 @@
+// Neo N3 transactions
+// ref: https://developers.neo.org/docs/n3/foundation/Transactions
+use std::error::Error;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::Value;
+use secp256k1::hashes::{sha256::Hash as Sha256Hash, Hash};
+use crate::crypto::eip712::encode_integer;
+
+pub const NETWORK_MAGIC_MAINNET: u32 = 860833102;
+pub const NETWORK_MAGIC_TESTNET: u32 = 894710606;
+const ADDRESS_VERSION: u8 = 0x35;  // N3 addresses start with N
+
+// WitnessScope
+const SCOPE_CUSTOM_CONTRACTS: u8 = 0x10;
+const SCOPE_CUSTOM_GROUPS: u8 = 0x20;
+const SCOPE_WITNESS_RULES: u8 = 0x40;
+
+pub struct Signer {
+    pub account: [u8; 20],  // script hash, little-endian as serialized
+    pub scopes: u8,
+    pub allowed_contracts: Vec<[u8; 20]>,
+    pub allowed_groups: Vec<[u8; 33]>,
+}
+
+pub enum Attribute {
+    HighPriority,
+    NotValidBefore(u32),
+    Conflicts([u8; 32]),
+}
+
+pub struct Witness {
+    pub invocation_script: Vec<u8>,
+    pub verification_script: Vec<u8>,
+}
+
+pub struct NeoTransaction {
+    pub version: u8,
+    pub nonce: u32,
+    pub system_fee: i64,
+    pub network_fee: i64,
+    pub valid_until_block: u32,
+    pub signers: Vec<Signer>,
+    pub attributes: Vec<Attribute>,
+    pub script: Vec<u8>,
+    pub witnesses: Vec<Witness>,
+}
+
+pub fn write_var_int(buffer: &mut Vec<u8>, value: u64) {
+    if value < 0xfd {
+        buffer.push(value as u8);
+    } else if value <= 0xffff {
+        buffer.push(0xfd);
+        buffer.extend((value as u16).to_le_bytes());
+    } else if value <= 0xffffffff {
+        buffer.push(0xfe);
+        buffer.extend((value as u32).to_le_bytes());
+    } else {
+        buffer.push(0xff);
+        buffer.extend(value.to_le_bytes());
+    }
+}
+
+pub fn write_var_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    write_var_int(buffer, bytes.len() as u64);
+    buffer.extend(bytes);
+}
+
+impl Signer {
+    pub fn serialize(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(self.account);
+        buffer.push(self.scopes);
+        if self.scopes & SCOPE_CUSTOM_CONTRACTS != 0 {
+            write_var_int(buffer, self.allowed_contracts.len() as u64);
+            for contract in &self.allowed_contracts {
+                buffer.extend(contract);
+            }
+        }
+        if self.scopes & SCOPE_CUSTOM_GROUPS != 0 {
+            write_var_int(buffer, self.allowed_groups.len() as u64);
+            for group in &self.allowed_groups {
+                buffer.extend(group);
+            }
+        }
+    }
+}
+
+impl Attribute {
+    pub fn serialize(&self, buffer: &mut Vec<u8>) {
+        match self {
+            Attribute::HighPriority => buffer.push(0x01),
+            Attribute::NotValidBefore(height) => {
+                buffer.push(0x20);
+                buffer.extend(height.to_le_bytes());
+            },
+            Attribute::Conflicts(hash) => {
+                buffer.push(0x21);
+                buffer.extend(hash);
+            },
+        }
+    }
+}
+
+impl Witness {
+    pub fn serialize(&self, buffer: &mut Vec<u8>) {
+        write_var_bytes(buffer, &self.invocation_script);
+        write_var_bytes(buffer, &self.verification_script);
+    }
+}
+
+impl NeoTransaction {
+    // Everything but the witnesses; this is what gets hashed and signed
+    pub fn serialize_unsigned(&self) -> Vec<u8> {
+        let mut buffer = vec![self.version];
+        buffer.extend(self.nonce.to_le_bytes());
+        buffer.extend(self.system_fee.to_le_bytes());
+        buffer.extend(self.network_fee.to_le_bytes());
+        buffer.extend(self.valid_until_block.to_le_bytes());
+        write_var_int(&mut buffer, self.signers.len() as u64);
+        for signer in &self.signers {
+            signer.serialize(&mut buffer);
+        }
+        write_var_int(&mut buffer, self.attributes.len() as u64);
+        for attribute in &self.attributes {
+            attribute.serialize(&mut buffer);
+        }
+        write_var_bytes(&mut buffer, &self.script);
+        buffer
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buffer = self.serialize_unsigned();
+        write_var_int(&mut buffer, self.witnesses.len() as u64);
+        for witness in &self.witnesses {
+            witness.serialize(&mut buffer);
+        }
+        buffer
+    }
+
+    // SHA-256 of the unsigned transaction, little-endian as serialized
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256Hash::hash(&self.serialize_unsigned()).to_byte_array()
+    }
+
+    // "0x..." as displayed by explorers and RPC, big-endian
+    pub fn hash_string(&self) -> String {
+        let mut hash = self.hash();
+        hash.reverse();
+        format!("0x{}", hex::encode(hash))
+    }
+
+    // network magic (u32 LE) || hash; signed with SHA-256 by Crypto.Sign
+    pub fn sign_data(&self, network: u32) -> Vec<u8> {
+        let mut data = network.to_le_bytes().to_vec();
+        data.extend(self.hash());
+        data
+    }
+
+    // Parse the JSON format of the Neo RPC (scripts in base64, hashes in 0x big-endian).
+    // Signers default to `default_account` with CalledByEntry when omitted.
+    pub fn from_json(tx: &Value, default_account: [u8; 20]) -> Result<NeoTransaction, Box<dyn Error>> {
+        let signers = match tx.get("signers").and_then(|s| s.as_array()) {
+            Some(signers) => signers.iter().map(parse_signer).collect::<Result<Vec<Signer>, Box<dyn Error>>>()?,
+            None => vec![Signer{ account: default_account, scopes: 0x01, allowed_contracts: vec![], allowed_groups: vec![] }],
+        };
+        let attributes = match tx.get("attributes").and_then(|a| a.as_array()) {
+            Some(attributes) => attributes.iter().map(parse_attribute).collect::<Result<Vec<Attribute>, Box<dyn Error>>>()?,
+            None => vec![],
+        };
+        let witnesses = match tx.get("witnesses").and_then(|w| w.as_array()) {
+            Some(witnesses) => witnesses.iter().map(parse_witness).collect::<Result<Vec<Witness>, Box<dyn Error>>>()?,
+            None => vec![],
+        };
+        let script = tx.get("script").and_then(|s| s.as_str()).ok_or("No script")?;
+        Ok(NeoTransaction{
+            version: in_range(optional_integer(tx, "version")?, "version")?,
+            nonce: in_range(optional_integer(tx, "nonce")?, "nonce")?,
+            system_fee: in_range(integer(tx, "sysfee").or_else(|_| integer(tx, "systemFee"))?, "sysfee")?,
+            network_fee: in_range(integer(tx, "netfee").or_else(|_| integer(tx, "networkFee"))?, "netfee")?,
+            valid_until_block: in_range(integer(tx, "validuntilblock").or_else(|_| integer(tx, "validUntilBlock"))?, "validuntilblock")?,
+            signers,
+            attributes,
+            script: BASE64.decode(script)?,
+            witnesses,
+        })
+    }
+}
+
+fn integer(tx: &Value, key: &str) -> Result<u64, Box<dyn Error>> {
+    let word = encode_integer(tx.get(key).ok_or(format!("No {}", key))?)?;
+    if word[..24].iter().any(|b| *b != 0) {
+        return Err(format!("Invalid {}", key).into());
+    }
+    Ok(u64::from_be_bytes(word[24..].try_into()?))
+}
+
+fn in_range<T: TryFrom<u64>>(value: u64, key: &str) -> Result<T, Box<dyn Error>> {
+    T::try_from(value).map_err(|_| format!("{} out of range", key).into())
+}
+
+fn optional_integer(tx: &Value, key: &str) -> Result<u64, Box<dyn Error>> {
+    match tx.get(key) {
+        Some(_) => integer(tx, key),
+        None => Ok(0),
+    }
+}
+
+// "0x<big-endian hex>" or a Neo address
+pub fn parse_script_hash(s: &str) -> Result<[u8; 20], Box<dyn Error>> {
+    let mut script_hash = [0u8; 20];
+    if let Some(h) = s.strip_prefix("0x") {
+        let bytes = hex::decode(h)?;
+        if bytes.len() != 20 {
+            return Err(format!("Invalid script hash {}", s).into());
+        }
+        script_hash.copy_from_slice(&bytes);
+        script_hash.reverse();
+    } else {
+        let decoded = bs58::decode(s).into_vec()?;
+        if decoded.len() != 25 || decoded[0] != ADDRESS_VERSION || Sha256Hash::hash(&Sha256Hash::hash(&decoded[..21]).to_byte_array())[..4] != decoded[21..] {
+            return Err(format!("Invalid address {}", s).into());
+        }
+        script_hash.copy_from_slice(&decoded[1..21]);
+    }
+    Ok(script_hash)
+}
+
+fn parse_scopes(scopes: &Value) -> Result<u8, Box<dyn Error>> {
+    if let Some(n) = scopes.as_u64() {
+        return in_range(n, "scopes");
+    }
+    let mut result = 0;
+    for scope in scopes.as_str().ok_or("Invalid scopes")?.split(',') {
+        result |= match scope.trim() {
+            "None" => 0x00,
+            "CalledByEntry" => 0x01,
+            "CustomContracts" => SCOPE_CUSTOM_CONTRACTS,
+            "CustomGroups" => SCOPE_CUSTOM_GROUPS,
+            "WitnessRules" => SCOPE_WITNESS_RULES,
+            "Global" => 0x80,
+            s => return Err(format!("Unknown scope {}", s).into()),
+        };
+    }
+    Ok(result)
+}
+
+fn parse_signer(signer: &Value) -> Result<Signer, Box<dyn Error>> {
+    let account = parse_script_hash(signer.get("account").and_then(|a| a.as_str()).ok_or("No signer account")?)?;
+    let scopes = parse_scopes(signer.get("scopes").unwrap_or(&Value::from("CalledByEntry")))?;
+    if scopes & SCOPE_WITNESS_RULES != 0 {
+        return Err("WitnessRules scope is not supported".into());
+    }
+    let mut allowed_contracts = vec![];
+    for contract in signer.get("allowedcontracts").or(signer.get("allowedContracts")).and_then(|c| c.as_array()).unwrap_or(&vec![]) {
+        allowed_contracts.push(parse_script_hash(contract.as_str().ok_or("Invalid allowed contract")?)?);
+    }
+    let mut allowed_groups = vec![];
+    for group in signer.get("allowedgroups").or(signer.get("allowedGroups")).and_then(|g| g.as_array()).unwrap_or(&vec![]) {
+        let bytes = hex::decode(group.as_str().ok_or("Invalid allowed group")?)?;
+        allowed_groups.push(bytes.try_into().map_err(|_| "Invalid allowed group")?);
+    }
+    Ok(Signer{ account, scopes, allowed_contracts, allowed_groups })
+}
+
+fn parse_attribute(attribute: &Value) -> Result<Attribute, Box<dyn Error>> {
+    match attribute.get("type").and_then(|t| t.as_str()) {
+        Some("HighPriority") => Ok(Attribute::HighPriority),
+        Some("NotValidBefore") => Ok(Attribute::NotValidBefore(in_range(integer(attribute, "height")?, "height")?)),
+        Some("Conflicts") => {
+            let hash = hex::decode(attribute.get("hash").and_then(|h| h.as_str()).ok_or("No conflicts hash")?.trim_start_matches("0x"))?;
+            let mut hash: [u8; 32] = hash.try_into().map_err(|_| "Invalid conflicts hash")?;
+            hash.reverse();
+            Ok(Attribute::Conflicts(hash))
+        },
+        t => Err(format!("Unsupported attribute {:?}", t).into()),
+    }
+}
+
+fn parse_witness(witness: &Value) -> Result<Witness, Box<dyn Error>> {
+    let invocation = witness.get("invocation").and_then(|i| i.as_str()).unwrap_or("");
+    let verification = witness.get("verification").and_then(|v| v.as_str()).unwrap_or("");
+    Ok(Witness{ invocation_script: BASE64.decode(invocation)?, verification_script: BASE64.decode(verification)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_var_int() {
+        let mut buffer = vec![];
+        write_var_int(&mut buffer, 0xfc);
+        write_var_int(&mut buffer, 0xfd);
+        write_var_int(&mut buffer, 0x10000);
+        assert_eq!(buffer, [0xfc, 0xfd, 0xfd, 0x00, 0xfe, 0x00, 0x00, 0x01, 0x00]);
+    }
+
+    #[test]
+    // ref: https://github.com/neo-project/neo/blob/master/tests/Neo.UnitTests/Network/P2P/Payloads/UT_Transaction.cs
+    // Transaction_Serialize_Deserialize_Simple
+    fn test_serialize() {
+        let tx: Value = serde_json::from_str(r#"{
+            "version": 0,
+            "nonce": 16909060,
+            "sysfee": "100000000",
+            "netfee": 1,
+            "validuntilblock": 16909060,
+            "signers": [{"account": "0x0000000000000000000000000000000000000000", "scopes": "None"}],
+            "attributes": [],
+            "script": "EQ==",
+            "witnesses": [{"invocation": "", "verification": ""}]
+        }"#).unwrap();
+        let tx = NeoTransaction::from_json(&tx, [0; 20]).unwrap();
+        assert_eq!(hex::encode(tx.serialize()), concat!(
+            "00",  // version
+            "04030201",  // nonce
+            "00e1f50500000000",  // system fee
+            "0100000000000000",  // network fee
+            "04030201",  // valid until block
+            "01000000000000000000000000000000000000000000",  // signer with scope None
+            "00",  // no attributes
+            "0111",  // PUSH1
+            "010000",  // empty witness
+        ));
+        assert_eq!(tx.sign_data(NETWORK_MAGIC_MAINNET)[..4], [0x4e, 0x45, 0x4f, 0x33]);  // "NEO3"
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let tx = |field: &str| -> Value {
+            let mut tx: Value = serde_json::from_str(r#"{"version": 0, "nonce": 1, "sysfee": 1, "netfee": 1, "validuntilblock": 1, "script": "EQ=="}"#).unwrap();
+            tx[field] = Value::from(u64::MAX);
+            tx
+        };
+        for field in ["version", "nonce", "sysfee", "netfee", "validuntilblock"] {
+            assert!(NeoTransaction::from_json(&tx(field), [0; 20]).is_err(), "{}", field);
+        }
+        let attribute: Value = serde_json::from_str(r#"{"type": "NotValidBefore", "height": 4294967296}"#).unwrap();
+        assert!(parse_attribute(&attribute).is_err());
+        assert!(parse_scopes(&Value::from(256)).is_err());
+    }
+
+    #[test]
+    fn test_parse_script_hash() {
+        let script_hash = parse_script_hash("NUrR2m4hRyYTdzFseDZzUKLhj5YLJtjEN2").unwrap();
+        let mut hex_script_hash = script_hash;
+        hex_script_hash.reverse();
+        assert_eq!(parse_script_hash(&format!("0x{}", hex::encode(hex_script_hash))).unwrap(), script_hash);
+        // The same hash as a Bitcoin address: valid checksum, version 0x00
+        assert!(parse_script_hash("19GKxUAfpozs8RXuknuZBCAKvJw3SiNYcd").is_err());
+    }
+}
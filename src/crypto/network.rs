@@ -0,0 +1,72 @@
+use std::error::Error;
+use std::str::FromStr;
+use crate::crypto::neo_transaction::{NETWORK_MAGIC_MAINNET, NETWORK_MAGIC_TESTNET};
+
+// Which network a deployment generates addresses for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Network {
+    pub fn bitcoin_address_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet | Network::Regtest => 0x6f,
+        }
+    }
+
+    pub fn bitcoin_p2sh_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet | Network::Regtest => 0xc4,
+        }
+    }
+
+    pub fn bitcoin_wif_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x80,
+            Network::Testnet | Network::Regtest => 0xef,
+        }
+    }
+
+    pub fn bitcoin_bech32_hrp(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "bc",
+            Network::Testnet => "tb",
+            Network::Regtest => "bcrt",
+        }
+    }
+
+    // Mainnet, Sepolia, and the default of local dev chains (Hardhat, Ganache)
+    pub fn default_ethereum_chain_id(&self) -> u64 {
+        match self {
+            Network::Mainnet => 1,
+            Network::Testnet => 11155111,
+            Network::Regtest => 1337,
+        }
+    }
+
+    // Private Neo networks pick their own magic; set NEO_NETWORK_MAGIC for them
+    pub fn default_neo_network_magic(&self) -> u32 {
+        match self {
+            Network::Mainnet => NETWORK_MAGIC_MAINNET,
+            Network::Testnet | Network::Regtest => NETWORK_MAGIC_TESTNET,
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Network, Box<dyn Error>> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "regtest" => Ok(Network::Regtest),
+            _ => Err(format!("Unknown network {}", s).into()),
+        }
+    }
+}
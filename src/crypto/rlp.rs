@@ -0,0 +1,88 @@
+// Recursive Length Prefix encoding
+// ref: https://ethereum.org/en/developers/docs/data-structures-and-encoding/rlp/
+
+#[derive(Clone)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+// Big-endian bytes without leading zeros; 0 is the empty string
+pub fn trim_leading_zeros(bytes: &[u8]) -> Vec<u8> {
+    let first_nonzero = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    bytes[first_nonzero..].to_vec()
+}
+
+fn encode_length(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+    let length_bytes = trim_leading_zeros(&length.to_be_bytes());
+    let mut result = vec![offset + 55 + length_bytes.len() as u8];
+    result.extend(length_bytes);
+    result
+}
+
+pub fn encode(item: &RlpItem) -> Vec<u8> {
+    match item {
+        RlpItem::Bytes(bytes) => {
+            if bytes.len() == 1 && bytes[0] < 0x80 {
+                return bytes.to_vec();
+            }
+            let mut result = encode_length(bytes.len(), 0x80);
+            result.extend(bytes);
+            result
+        },
+        RlpItem::List(items) => {
+            let payload: Vec<u8> = items.iter().flat_map(encode).collect();
+            let mut result = encode_length(payload.len(), 0xc0);
+            result.extend(payload);
+            result
+        },
+    }
+}
+
+impl From<u64> for RlpItem {
+    fn from(v: u64) -> RlpItem {
+        RlpItem::Bytes(trim_leading_zeros(&v.to_be_bytes()))
+    }
+}
+
+impl From<&[u8]> for RlpItem {
+    fn from(v: &[u8]) -> RlpItem {
+        RlpItem::Bytes(v.to_vec())
+    }
+}
+
+impl From<&str> for RlpItem {
+    fn from(v: &str) -> RlpItem {
+        RlpItem::Bytes(v.as_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // ref: https://ethereum.org/en/developers/docs/data-structures-and-encoding/rlp/#examples
+    fn test_encode() {
+        assert_eq!(encode(&"dog".into()), b"\x83dog");
+        assert_eq!(encode(&RlpItem::List(vec!["cat".into(), "dog".into()])), b"\xc8\x83cat\x83dog");
+        assert_eq!(encode(&"".into()), [0x80]);
+        assert_eq!(encode(&RlpItem::List(vec![])), [0xc0]);
+        assert_eq!(encode(&0u64.into()), [0x80]);
+        assert_eq!(encode(&15u64.into()), [0x0f]);
+        assert_eq!(encode(&1024u64.into()), [0x82, 0x04, 0x00]);
+        let set = RlpItem::List(vec![
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![RlpItem::List(vec![])]),
+            RlpItem::List(vec![RlpItem::List(vec![]), RlpItem::List(vec![RlpItem::List(vec![])])]),
+        ]);
+        assert_eq!(encode(&set), [0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0]);
+        let lorem = "Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let mut expected = vec![0xb8, 0x38];
+        expected.extend(lorem.as_bytes());
+        assert_eq!(encode(&lorem.into()), expected);
+    }
+}
@@ -1,9 +1,89 @@
+use std::fmt;
+use hmac::{Hmac, Mac};
 use rand::rngs::OsRng;
+use sha2::Sha256;
 use secp256k1::Secp256k1;
 use secp256k1::hashes::{sha256::Hash as Sha256Hash, Hash};
 
 pub const DEFAULT_VERSION_BYTE: u8 = 0x80;
 
+#[derive(Debug, PartialEq)]
+pub enum WifError {
+    InvalidBase58,
+    InvalidLength(usize),
+    InvalidChecksum,
+    InvalidCompressionFlag(u8),
+    Uncompressed,
+    UnexpectedVersion(u8),
+}
+
+impl fmt::Display for WifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WifError::InvalidBase58 => write!(f, "WIF is not valid base58"),
+            WifError::InvalidLength(len) => write!(f, "WIF payload has invalid length {}", len),
+            WifError::InvalidChecksum => write!(f, "WIF checksum mismatch"),
+            WifError::InvalidCompressionFlag(flag) => write!(f, "WIF has invalid compression flag 0x{:02x}", flag),
+            WifError::Uncompressed => write!(f, "WIF is not for a compressed public key"),
+            WifError::UnexpectedVersion(version) => write!(f, "WIF has unexpected version byte 0x{:02x}", version),
+        }
+    }
+}
+
+impl std::error::Error for WifError {}
+
+pub struct DecodedWif {
+    pub version_byte: u8,
+    pub secret_key: [u8; 32],
+    pub compressed: bool,
+}
+
+// version || key (32) || [0x01 if compressed] || checksum (4)
+pub fn decode_wif(wif: &str) -> Result<DecodedWif, WifError> {
+    let bytes = bs58::decode(wif).into_vec().map_err(|_| WifError::InvalidBase58)?;
+    if bytes.len() != 37 && bytes.len() != 38 {
+        return Err(WifError::InvalidLength(bytes.len()));
+    }
+    let (payload, checksum) = bytes.split_at(bytes.len() - 4);
+    let expected = &Sha256Hash::hash(
+        &Sha256Hash::hash(payload)
+            .to_byte_array()
+    )[..4];
+    if checksum != expected {
+        return Err(WifError::InvalidChecksum);
+    }
+    let compressed = match payload.get(33) {
+        None => false,
+        Some(0x01) => true,
+        Some(flag) => return Err(WifError::InvalidCompressionFlag(*flag)),
+    };
+    let mut secret_key = [0u8; 32];
+    secret_key.copy_from_slice(&payload[1..33]);
+    Ok(DecodedWif{ version_byte: payload[0], secret_key, compressed })
+}
+
+// Secret key of a compressed WIF whose version is one of `version_bytes`
+pub fn decode_compressed_wif(wif: &str, version_bytes: &[u8]) -> Result<[u8; 32], WifError> {
+    let decoded = decode_wif(wif)?;
+    if !version_bytes.contains(&decoded.version_byte) {
+        return Err(WifError::UnexpectedVersion(decoded.version_byte));
+    }
+    if !decoded.compressed {
+        return Err(WifError::Uncompressed);
+    }
+    Ok(decoded.secret_key)
+}
+
+// ed25519 seed of an account made before HD derivation: HMAC-SHA256 of its
+// secp256k1 secret, keyed by a label naming the chain. The secret itself is
+// not used as the seed, so that no two curves or chains share a key.
+pub fn legacy_ed25519_seed(wif: &str, label: &str) -> Result<[u8; 32], WifError> {
+    let secret_key = decode_compressed_wif(wif, &[DEFAULT_VERSION_BYTE])?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(label.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(&secret_key);
+    Ok(mac.finalize().into_bytes().into())
+}
+
 pub fn bytes_32_to_wif(mut bytes: Vec<u8>, compressed: bool, version_byte: u8) -> String {
     bytes.insert(0, version_byte);
     if compressed {
@@ -14,25 +94,22 @@ pub fn bytes_32_to_wif(mut bytes: Vec<u8>, compressed: bool, version_byte: u8) -
             .to_byte_array()
     )[..4];
     bytes.extend(checksum);
-    let wif = bs58::encode(bytes).into_string();
-    return wif;
+    bs58::encode(bytes).into_string()
 }
 
 pub fn new_secret_key_32bytes() -> Vec<u8> {
     let secp = Secp256k1::new();
     let (secret_key, _) = secp.generate_keypair(&mut OsRng);
-    let secret_key_bytes = secret_key.secret_bytes().to_vec();
-    return secret_key_bytes;
+    secret_key.secret_bytes().to_vec()
 }
 
 pub fn new_secret_key_wif(compressed: bool, version_byte: u8) -> String {
     let secret_key_bytes = new_secret_key_32bytes();
-    let wif = bytes_32_to_wif(secret_key_bytes, compressed, version_byte);
-    return wif;
+    bytes_32_to_wif(secret_key_bytes, compressed, version_byte)
 }
 
 pub fn new_secret_key_wif_default_version(compressed: bool) -> String {
-    return new_secret_key_wif(compressed, DEFAULT_VERSION_BYTE);
+    new_secret_key_wif(compressed, DEFAULT_VERSION_BYTE)
 }
 
 #[cfg(test)]
@@ -63,4 +140,27 @@ mod tests {
         let uncompressed_key = bytes_32_to_wif(bytes, false, DEFAULT_VERSION_BYTE);
         assert_eq!(uncompressed_key, "5JJASvwSqwECbik1kvu6jvG1mGPCPUu7e2Mzft6TUMdo2NW4ZLy");
     }
+
+    #[test]
+    fn test_decode_wif() {
+        let bytes = hex_string_to_u8_vector("3f4c53c9a69fc70b008067b842c946e444f035aef4ffb8878e044084506f893e").unwrap();
+        let secret_key = decode_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", &[DEFAULT_VERSION_BYTE]).unwrap();
+        assert_eq!(secret_key.to_vec(), bytes);
+        let decoded = decode_wif("5JJASvwSqwECbik1kvu6jvG1mGPCPUu7e2Mzft6TUMdo2NW4ZLy").unwrap();
+        assert_eq!(decoded.secret_key.to_vec(), bytes);
+        assert!(!decoded.compressed);
+
+        assert_eq!(decode_compressed_wif("5JJASvwSqwECbik1kvu6jvG1mGPCPUu7e2Mzft6TUMdo2NW4ZLy", &[DEFAULT_VERSION_BYTE]).err(), Some(WifError::Uncompressed));
+        assert_eq!(decode_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd", &[0xef]).err(), Some(WifError::UnexpectedVersion(0x80)));
+        // last character changed
+        assert_eq!(decode_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyre").err(), Some(WifError::InvalidChecksum));
+        assert_eq!(decode_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoR").err(), Some(WifError::InvalidLength(35)));
+        assert_eq!(decode_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRky0").err(), Some(WifError::InvalidBase58));
+        let mut bad_flag = bytes.clone();
+        bad_flag.insert(0, DEFAULT_VERSION_BYTE);
+        bad_flag.push(0x02);
+        let checksum = Sha256Hash::hash(&Sha256Hash::hash(&bad_flag).to_byte_array()).to_byte_array();
+        bad_flag.extend(&checksum[..4]);
+        assert_eq!(decode_wif(&bs58::encode(bad_flag).into_string()).err(), Some(WifError::InvalidCompressionFlag(0x02)));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,203 @@
+use std::error::Error;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use crate::crypto::secret_key::legacy_ed25519_seed;
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
+
+// Off-chain messages of solana-sdk (version 0), as `solana sign-offchain-message`.
+// No transaction message starts with 0xff.
+const SIGNING_DOMAIN: &[u8; 16] = b"\xffsolana offchain";
+const HEADER_LEN: usize = 20;
+const MAX_LEN: usize = u16::MAX as usize - HEADER_LEN;
+const MAX_LEN_LEDGER: usize = 1232 - HEADER_LEN;  // fits a packet, for hardware wallets
+
+// signing domain || version 0 || format || u16 length || message
+pub fn offchain_message(message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let format: u8 = if message.is_empty() {
+        return Err("Empty message".into());
+    } else if message.len() <= MAX_LEN_LEDGER && message.iter().all(|b| (0x20..=0x7e).contains(b)) {
+        0  // restricted ASCII
+    } else if std::str::from_utf8(message).is_err() || message.len() > MAX_LEN {
+        return Err(format!("Off-chain messages are UTF-8 of at most {} bytes", MAX_LEN).into());
+    } else if message.len() <= MAX_LEN_LEDGER {
+        1  // limited UTF-8
+    } else {
+        2  // extended UTF-8
+    };
+    let mut data = SIGNING_DOMAIN.to_vec();
+    data.extend([0, format]);
+    data.extend((message.len() as u16).to_le_bytes());
+    data.extend(message);
+    Ok(data)
+}
+
+pub struct SolanaKeypair {
+    pub secret_key: SigningKey,
+    pub secret_key_base58: String,  // seed || public key, as exported by Phantom and Solflare
+    pub public_key: String,
+    pub address: String,  // base58 of the public key
+}
+
+impl SolanaKeypair {
+    pub fn new() -> SolanaKeypair {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        SolanaKeypair::from_keypair(SigningKey::from_bytes(&seed))
+    }
+
+    // Accounts made before HD derivation, from a seed derived from their secret
+    pub fn from_compressed_wif(wif: &str) -> Result<SolanaKeypair, Box<dyn Error>> {
+        Self::from_secret_key_slice(&legacy_ed25519_seed(wif, "solana legacy ed25519 seed")?)
+    }
+
+    pub fn from_secret_key_slice(s: &[u8]) -> Result<SolanaKeypair, Box<dyn Error>> {
+        let seed: [u8; 32] = s.try_into().map_err(|_| "ed25519 seed must be 32 bytes")?;
+        Ok(SolanaKeypair::from_keypair(SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn from_keypair(secret_key: SigningKey) -> SolanaKeypair {
+        let public_key_bytes = secret_key.verifying_key().to_bytes();
+        let address = bs58::encode(public_key_bytes).into_string();
+
+        let public_key_string: String = public_key_bytes.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join("");
+
+        let secret_key_base58 = bs58::encode(secret_key.to_keypair_bytes()).into_string();
+
+        SolanaKeypair{ secret_key, secret_key_base58, public_key: public_key_string, address }
+    }
+
+    // 64 bytes seed || public key, the format of solana-keygen JSON files
+    pub fn to_keypair_bytes(&self) -> [u8; 64] {
+        self.secret_key.to_keypair_bytes()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        self.secret_key.sign(message).to_bytes()
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return false;
+        };
+        self.secret_key.verifying_key().verify(message, &signature).is_ok()
+    }
+
+    pub fn sign_offchain_message(&self, message: &[u8]) -> Result<[u8; 64], Box<dyn Error>> {
+        Ok(self.sign(&offchain_message(message)?))
+    }
+
+    pub fn verify_offchain_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        offchain_message(message).is_ok_and(|message| self.verify(&message, signature))
+    }
+}
+
+impl ChainKeypair for SolanaKeypair {
+    fn generate(_network: Network) -> SolanaKeypair {
+        SolanaKeypair::new()
+    }
+
+    fn from_wif(wif: &str, _network: Network) -> Result<SolanaKeypair, Box<dyn Error>> {
+        SolanaKeypair::from_compressed_wif(wif)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], _network: Network) -> Result<SolanaKeypair, Box<dyn Error>> {
+        SolanaKeypair::from_secret_key_slice(s)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Ed25519
+    }
+
+    // Phantom and Solflare
+    fn hd_path(account: u32, _network: Network) -> String {
+        format!("m/44'/501'/{}'/0'", account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // base58 of the 64-byte keypair, as Phantom and Solflare import
+    fn export_secret_key(&self) -> String {
+        self.secret_key_base58.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_offchain_message(message)?.to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_offchain_message(message, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // ref: https://datatracker.ietf.org/doc/html/rfc8032#section-7.1 TEST 1
+    fn test_addr() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = SolanaKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(keypair.public_key, "D75A980182B10AB7D54BFED3C964073A0EE172F3DAA62325AF021A68F707511A");
+        assert_eq!(keypair.address, "FVen3X669xLzsi6N2V91DoiyzHzg1uAgqiT8jZ9nS96Z");
+        assert_eq!(keypair.secret_key_base58, "49W385L4rePHy6PAaQUovbD2aacgN4HsKXSMeUzRg4fmwXszN91JuMFrQRj3vMDpZuRF3ZknQBuRBoWQJEfXstMw");
+        assert_eq!(keypair.to_keypair_bytes()[..32], seed[..]);
+    }
+
+    #[test]
+    // HMAC-SHA256 checked against Python's hmac module
+    fn test_legacy_seed() {
+        let keypair = SolanaKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        assert_eq!(hex::encode(keypair.secret_key.to_bytes()), "6fccc17fea089c5abd89726b2ca07fd660860abe1b0ddc56e718b68bcfe0dab3");
+    }
+
+    #[test]
+    fn test_sign() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = SolanaKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(hex::encode(keypair.sign(b"")), "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b");
+    }
+
+    #[test]
+    // Checked against the ed25519 of pyca/cryptography
+    fn test_sign_offchain_message() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = SolanaKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(hex::encode(offchain_message(b"hello").unwrap()), "ff736f6c616e61206f6666636861696e0000050068656c6c6f");
+        assert_eq!(hex::encode(keypair.sign_offchain_message(b"hello").unwrap()), "77d686ecba91068e833bcdef645fa0dc751de94e36fa46c0dddcc7ed3fcef1f890b53d6e8bf804a6cf160810954dc114d144e0e04013e1b1494d375e09115c04");
+        assert_eq!(hex::encode(offchain_message("h\u{e9}llo".as_bytes()).unwrap()), "ff736f6c616e61206f6666636861696e0001060068c3a96c6c6f");
+        assert_eq!(offchain_message(&vec![b'a'; 2000]).unwrap()[17], 2);
+        assert!(offchain_message(b"").is_err());
+        assert!(offchain_message(&[0xff, 0xfe]).is_err());
+        assert!(offchain_message(&vec![b'a'; MAX_LEN + 1]).is_err());
+    }
+
+    #[test]
+    fn test_from_wif() {
+        let wif = "KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd";
+        let keypair = SolanaKeypair::from_compressed_wif(wif).unwrap();
+        assert_eq!(keypair.address, SolanaKeypair::from_compressed_wif(wif).unwrap().address);
+        assert_eq!(bs58::decode(&keypair.address).into_vec().unwrap().len(), 32);
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = SolanaKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&SolanaKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
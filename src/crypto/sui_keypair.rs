@@ -0,0 +1,212 @@
+use std::error::Error;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use blake2::{Blake2b, Digest, digest::consts::U32};
+use crate::crypto::bech32::{encode_bytes, Variant};
+use crate::crypto::secret_key::legacy_ed25519_seed;
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
+
+type Blake2b256 = Blake2b<U32>;
+
+const ED25519_FLAG: u8 = 0x00;
+
+// Intent scopes, followed by version 0 and app id Sui
+const INTENT_TRANSACTION_DATA: [u8; 3] = [0, 0, 0];
+const INTENT_PERSONAL_MESSAGE: [u8; 3] = [3, 0, 0];
+
+pub struct SuiKeypair {
+    pub secret_key: SigningKey,
+    pub public_key: String,
+    pub address: String,  // 0x || BLAKE2b-256(flag || public key)
+}
+
+fn write_uleb128(buf: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+// BLAKE2b-256(intent || message), what Sui signs
+fn intent_digest(intent: [u8; 3], message: &[u8]) -> [u8; 32] {
+    let mut hasher = Blake2b256::new();
+    hasher.update(intent);
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+impl SuiKeypair {
+    pub fn new() -> SuiKeypair {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        SuiKeypair::from_keypair(SigningKey::from_bytes(&seed))
+    }
+
+    // Accounts made before HD derivation, from a seed derived from their secret
+    pub fn from_compressed_wif(wif: &str) -> Result<SuiKeypair, Box<dyn Error>> {
+        Self::from_secret_key_slice(&legacy_ed25519_seed(wif, "sui legacy ed25519 seed")?)
+    }
+
+    pub fn from_secret_key_slice(s: &[u8]) -> Result<SuiKeypair, Box<dyn Error>> {
+        let seed: [u8; 32] = s.try_into().map_err(|_| "ed25519 seed must be 32 bytes")?;
+        Ok(SuiKeypair::from_keypair(SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn from_keypair(secret_key: SigningKey) -> SuiKeypair {
+        let public_key_bytes = secret_key.verifying_key().to_bytes();
+        let mut hasher = Blake2b256::new();
+        hasher.update([ED25519_FLAG]);
+        hasher.update(public_key_bytes);
+        let address = format!("0x{}", hex::encode(hasher.finalize()));
+
+        let public_key_string: String = public_key_bytes.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join("");
+
+        SuiKeypair{ secret_key, public_key: public_key_string, address }
+    }
+
+    pub fn secret_key_bech32(&self) -> String {
+        let mut bytes = vec![ED25519_FLAG];
+        bytes.extend(self.secret_key.to_bytes());
+        encode_bytes("suiprivkey", &bytes, Variant::Bech32)
+    }
+
+    // flag || signature || public key, the format Sui RPCs take (base64)
+    fn serialized_signature(&self, digest: &[u8; 32]) -> [u8; 97] {
+        let mut result = [0u8; 97];
+        result[0] = ED25519_FLAG;
+        result[1..65].copy_from_slice(&self.secret_key.sign(digest).to_bytes());
+        result[65..].copy_from_slice(&self.secret_key.verifying_key().to_bytes());
+        result
+    }
+
+    // signPersonalMessage: the message is BCS serialized as vector<u8>
+    pub fn sign_personal_message(&self, message: &[u8]) -> [u8; 97] {
+        let mut bcs = Vec::new();
+        write_uleb128(&mut bcs, message.len());
+        bcs.extend_from_slice(message);
+        self.serialized_signature(&intent_digest(INTENT_PERSONAL_MESSAGE, &bcs))
+    }
+
+    // A serialized signature of sign_personal_message, by this key
+    pub fn verify_personal_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        let Ok(serialized) = <&[u8; 97]>::try_from(signature) else {
+            return false;
+        };
+        let public_key = self.secret_key.verifying_key();
+        if serialized[0] != ED25519_FLAG || serialized[65..] != public_key.to_bytes() {
+            return false;
+        }
+        let mut bcs = Vec::new();
+        write_uleb128(&mut bcs, message.len());
+        bcs.extend_from_slice(message);
+        let signature = Signature::from_bytes(serialized[1..65].try_into().expect("64 bytes"));
+        public_key.verify(&intent_digest(INTENT_PERSONAL_MESSAGE, &bcs), &signature).is_ok()
+    }
+
+    // BCS TransactionData, as built by the SDKs
+    pub fn sign_transaction(&self, transaction_data: &[u8]) -> [u8; 97] {
+        self.serialized_signature(&intent_digest(INTENT_TRANSACTION_DATA, transaction_data))
+    }
+}
+
+// sign() is signPersonalMessage, returning the serialized signature
+impl ChainKeypair for SuiKeypair {
+    fn generate(_network: Network) -> SuiKeypair {
+        SuiKeypair::new()
+    }
+
+    fn from_wif(wif: &str, _network: Network) -> Result<SuiKeypair, Box<dyn Error>> {
+        SuiKeypair::from_compressed_wif(wif)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], _network: Network) -> Result<SuiKeypair, Box<dyn Error>> {
+        SuiKeypair::from_secret_key_slice(s)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Ed25519
+    }
+
+    fn hd_path(account: u32, _network: Network) -> String {
+        format!("m/44'/784'/{}'/0'/0'", account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // suiprivkey bech32 of flag || seed (SIP-15), as Sui wallets import
+    fn export_secret_key(&self) -> String {
+        self.secret_key_bech32()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_personal_message(message).to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_personal_message(message, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+    #[test]
+    // RFC 8032 TEST 1 key
+    fn test_addr() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = SuiKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(keypair.address, "0x304af458e90e97c841685b8cbbc59b909f3e2cf150df590ada4c81452c29737d");
+        assert_eq!(keypair.secret_key_bech32(), "suiprivkey1qzwkrvvaal745c96s390fyhv9nzygjw9d9any6gewqa6cqcu4elkqqfr3zg");
+    }
+
+    #[test]
+    // HMAC-SHA256 checked against Python's hmac module
+    fn test_legacy_seed() {
+        let keypair = SuiKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        assert_eq!(hex::encode(keypair.secret_key.to_bytes()), "c762a701f109c8da4499e755e5ef65d9136c2c66cfd35dcff21bbbe492a9f6d3");
+    }
+
+    #[test]
+    fn test_sign_personal_message() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = SuiKeypair::from_secret_key_slice(&seed).unwrap();
+        assert_eq!(BASE64.encode(keypair.sign_personal_message(b"hello")), "AGfYvHp3Km9JWEp41qKVC4hw01zUPO2j5pe1d3lHcVzPBnLmyYt/92sJxG3r98oOxhxGWq93G3EA5JKdMDtsBAHXWpgBgrEKt9VL/tPJZAc6DuFy89qmIyWvAhpo9wdRGg==");
+        let mut bcs = Vec::new();
+        write_uleb128(&mut bcs, 300);
+        assert_eq!(bcs, [0xac, 0x02]);
+    }
+
+    #[test]
+    // Checked against the ed25519 of pyca/cryptography
+    fn test_sign_transaction() {
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let keypair = SuiKeypair::from_secret_key_slice(&seed).unwrap();
+        let signature = keypair.sign_transaction(&[1, 2, 3, 4, 5]);
+        assert_eq!(signature[0], ED25519_FLAG);
+        assert_eq!(hex::encode(&signature[1..65]), "716e324319bee1ef7c8cb56b6f492a47952202e671d99ae20615dec5a2c0dd8c95608dd9c1c49381bd6b5d884a26f37168309022c07aae6300006c19c1f81f0d");
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = SuiKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&SuiKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
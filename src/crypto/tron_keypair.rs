@@ -0,0 +1,149 @@
+use std::error::Error;
+use rand::rngs::OsRng;
+use secp256k1;
+use secp256k1::Secp256k1;
+use sha3::{Digest, Keccak256};
+use crate::crypto::bitcoin_keypair::base58check;
+use crate::crypto::ethereum_keypair::{keccak_address, sign_recoverable, verify_recoverable};
+use crate::crypto::secret_key::{bytes_32_to_wif, decode_compressed_wif, DEFAULT_VERSION_BYTE};
+use crate::crypto::chain_keypair::ChainKeypair;
+use crate::crypto::hd::Curve;
+use crate::crypto::network::Network;
+
+// Same on Nile and Shasta testnets
+const ADDRESS_VERSION_BYTE: u8 = 0x41;
+
+pub struct TronKeypair {
+    pub secret_key: secp256k1::SecretKey,
+    pub secret_key_compressed_wif: String,
+    pub public_key: String,
+    pub address: String,  // Base58Check of 0x41 || Ethereum address, T...
+}
+
+impl TronKeypair {
+    pub fn new() -> TronKeypair {
+        let secp = Secp256k1::new();
+        let (secret_key, public_key) = secp.generate_keypair(&mut OsRng);
+        TronKeypair::from_keypair(secret_key, public_key)
+    }
+
+    pub fn from_compressed_wif(wif: &str) -> Result<TronKeypair, Box<dyn Error>> {
+        let secret_key_bytes = decode_compressed_wif(wif, &[DEFAULT_VERSION_BYTE])?;
+        TronKeypair::from_secret_key_slice(&secret_key_bytes)
+    }
+
+    pub fn from_secret_key_slice(s: &[u8]) -> Result<TronKeypair, Box<dyn Error>> {
+        let secp = Secp256k1::new();
+        let sk = secp256k1::SecretKey::from_slice(s)?;
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &sk);
+        Ok(TronKeypair::from_keypair(sk, public_key))
+    }
+
+    pub fn from_keypair(secret_key: secp256k1::SecretKey, public_key: secp256k1::PublicKey) -> TronKeypair {
+        let mut payload = vec![ADDRESS_VERSION_BYTE];
+        payload.extend(keccak_address(&public_key));
+        let address = base58check(payload);
+
+        let public_key_string: String = public_key.serialize().iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join("");
+
+        let secret_key_bytes = secret_key.secret_bytes().to_vec();
+        let secret_key_compressed_wif = bytes_32_to_wif(secret_key_bytes, true, DEFAULT_VERSION_BYTE);
+
+        TronKeypair{ secret_key, secret_key_compressed_wif, public_key: public_key_string, address }
+    }
+
+    // TIP-191 signMessageV2: keccak256("\x19TRON Signed Message:\n" || len(message) || message)
+    pub fn hash_message(message: &[u8]) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(format!("\x19TRON Signed Message:\n{}", message.len()).as_bytes());
+        hasher.update(message);
+        hasher.finalize().into()
+    }
+
+    pub fn sign_message(&self, message: &[u8]) -> [u8; 65] {
+        sign_recoverable(&self.secret_key, Self::hash_message(message))
+    }
+
+    pub fn verify_message(&self, message: &[u8], signature: &[u8]) -> bool {
+        verify_recoverable(&self.secret_key, Self::hash_message(message), signature)
+    }
+}
+
+impl ChainKeypair for TronKeypair {
+    fn generate(_network: Network) -> TronKeypair {
+        TronKeypair::new()
+    }
+
+    fn from_wif(wif: &str, _network: Network) -> Result<TronKeypair, Box<dyn Error>> {
+        TronKeypair::from_compressed_wif(wif)
+    }
+
+    fn from_secret_key_bytes(s: &[u8], _network: Network) -> Result<TronKeypair, Box<dyn Error>> {
+        TronKeypair::from_secret_key_slice(s)
+    }
+
+    fn hd_curve() -> Curve {
+        Curve::Secp256k1
+    }
+
+    fn hd_path(account: u32, _network: Network) -> String {
+        format!("m/44'/195'/0'/0/{}", account)
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    // hex, as TronLink imports
+    fn export_secret_key(&self) -> String {
+        hex::encode(self.secret_key.secret_bytes())
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.sign_message(message).to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.verify_message(message, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_addr() {
+        let keypair = TronKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        assert_eq!(keypair.address, "TJSiYrPnHxXKLDUSVqhQ7ditSUcp7bznLg");
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let keypair = TronKeypair::from_secret_key_slice(&one).unwrap();
+        assert_eq!(keypair.address, "TMVQGm1qAQYVdetCeGRRkTWYYrLXuHK2HC");
+    }
+
+    #[test]
+    // Checked against an independent Python implementation of signMessageV2
+    // (Keccak-256, RFC 6979) that reproduces the EIP-155 example
+    fn test_sign_message() {
+        let keypair = TronKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap();
+        assert_eq!(hex::encode(keypair.sign_message(b"hello")), "1438f17c4a45e6d2a7e18ecb15aa5c06181aea1960b234f4aed9c21a8caca5030b8ad20defb8da72b6ed9279e7be0e0e3eac58636a9ebaaaa6fdf66667fe0df91b");
+        assert_eq!(keypair.sign(b"hello").unwrap(), keypair.sign_message(b"hello").to_vec());
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let keypair = TronKeypair::generate(Network::Mainnet);
+        let signature = ChainKeypair::sign(&keypair, b"hello").unwrap();
+        assert!(ChainKeypair::verify(&keypair, b"hello", &signature));
+        assert!(!ChainKeypair::verify(&keypair, b"hellO", &signature));
+        assert!(!ChainKeypair::verify(&TronKeypair::generate(Network::Mainnet), b"hello", &signature));
+    }
+}
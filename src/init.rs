@@ -1,22 +1,40 @@
-use migration::backend;
-use sea_orm::{ConnectionTrait, Database, DatabaseConnection, EntityTrait, Schema};
-use entity::{self, user};
+use std::error::Error;
+use entity::user;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use crate::kms::KeyManager;
+use crate::utils::user_secrets::UserSecrets;
 
-pub async fn create_tables_if_not_exists<E>(db: &DatabaseConnection, table: E) 
-where E: EntityTrait,
-{
-    let backend = db.get_database_backend();
-    let schema = Schema::new(backend);
-    let mut table_create_statement = schema.create_table_from_entity(table);
-    table_create_statement.if_not_exists();
-    if let Err(table_create_result) = db.execute(backend.build(&table_create_statement)).await {
-        println!("{}", table_create_result);
+// Applies pending migrations, or refuses to run on a database that needs them
+pub async fn migrate(db: &DatabaseConnection, run_migrations: bool) -> Result<(), Box<dyn Error>> {
+    if run_migrations {
+        Migrator::up(db, None).await?;
+        return Ok(());
     }
-    let mut index_create_statements = schema.create_index_from_entity(table);
-    for s in &mut index_create_statements {
-        s.if_not_exists();
-        if let Err(index_create_result) = db.execute(backend.build(s)).await {
-            println!("{}", index_create_result);
-        }
+    let pending = Migrator::get_pending_migrations(db).await?;
+    if !pending.is_empty() {
+        let names: Vec<&str> = pending.iter().map(|m| m.name()).collect();
+        return Err(format!("Pending migrations {}; apply them or set RUN_MIGRATIONS=true", names.join(", ")).into());
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+// Encrypt the rows stored before encryption at rest
+pub async fn encrypt_plaintext_users(db: &DatabaseConnection, key_manager: &dyn KeyManager) -> Result<u64, Box<dyn Error>> {
+    let users = user::Entity::find()
+        .filter(user::Column::DataKey.is_null())
+        .all(db)
+        .await?;
+    let mut count = 0;
+    for user in users {
+        let encrypted = UserSecrets::decrypt(&user, key_manager).await?.encrypt(key_manager).await?;
+        count += user::Entity::update_many()
+            .set(encrypted)
+            .filter(user::Column::Id.eq(user.id))
+            .filter(user::Column::DataKey.is_null())
+            .exec(db)
+            .await?
+            .rows_affected;
+    }
+    Ok(count)
+}
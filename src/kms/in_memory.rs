@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use async_trait::async_trait;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use crate::crypto::envelope::{open, seal, WrappedKey};
+use super::{KeyManager, KmsError};
+
+// Master keys by version, held in process memory. Used for tests and as
+// the master keys of LocalFileKeyManager once its file is decrypted.
+// Data keys are wrapped with AES-256-GCM, the version as associated data.
+pub struct InMemoryKeyManager {
+    keys: BTreeMap<u32, [u8; 32]>,
+}
+
+impl InMemoryKeyManager {
+    // A single random master key of version 1
+    pub fn new() -> InMemoryKeyManager {
+        let mut keys = InMemoryKeyManager{ keys: BTreeMap::new() };
+        keys.add_version();
+        keys
+    }
+
+    pub fn from_keys(keys: BTreeMap<u32, [u8; 32]>) -> Result<InMemoryKeyManager, String> {
+        if keys.is_empty() {
+            return Err("No master key configured".to_string());
+        }
+        Ok(InMemoryKeyManager{ keys })
+    }
+
+    pub fn keys(&self) -> &BTreeMap<u32, [u8; 32]> {
+        &self.keys
+    }
+
+    pub fn current_version(&self) -> u32 {
+        *self.keys.keys().last().expect("at least one master key")
+    }
+
+    pub fn wrap_key(&self, data_key: &[u8]) -> WrappedKey {
+        let version = self.current_version();
+        let ciphertext = seal(&self.keys[&version], data_key, &version.to_be_bytes());
+        WrappedKey{ version, ciphertext }
+    }
+
+    pub fn unwrap_key(&self, wrapped: &WrappedKey) -> Result<Vec<u8>, KmsError> {
+        let key = self.keys.get(&wrapped.version).ok_or(KmsError::UnknownMasterKey(wrapped.version))?;
+        open(key, &wrapped.ciphertext, &wrapped.version.to_be_bytes()).map_err(|_| KmsError::UnwrapFailed)
+    }
+
+    // Adds a random master key one version above the current one, and returns its version
+    pub fn add_version(&mut self) -> u32 {
+        let version = self.keys.keys().last().map_or(1, |v| v + 1);
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        self.keys.insert(version, key);
+        version
+    }
+
+    // Undoes add_version; the last key is never removed
+    pub fn remove_version(&mut self, version: u32) {
+        if self.keys.len() > 1 {
+            self.keys.remove(&version);
+        }
+    }
+}
+
+// "1:<hex of 32 bytes>,2:<hex of 32 bytes>"; the highest version is current
+impl FromStr for InMemoryKeyManager {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<InMemoryKeyManager, String> {
+        let mut keys = BTreeMap::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (version, key) = entry.split_once(':').ok_or("Master keys must be given as <version>:<hex key>")?;
+            let version = version.parse::<u32>().map_err(|e| format!("Invalid master key version {}: {}", version, e))?;
+            let key: [u8; 32] = hex::decode(key).ok()
+                .and_then(|k| k.try_into().ok())
+                .ok_or(format!("Master key {} must be 32 bytes of hex", version))?;
+            if keys.insert(version, key).is_some() {
+                return Err(format!("Master key {} given twice", version));
+            }
+        }
+        InMemoryKeyManager::from_keys(keys)
+    }
+}
+
+#[async_trait]
+impl KeyManager for InMemoryKeyManager {
+    fn current_version(&self) -> u32 {
+        InMemoryKeyManager::current_version(self)
+    }
+
+    async fn wrap(&self, data_key: &[u8]) -> Result<WrappedKey, KmsError> {
+        Ok(self.wrap_key(data_key))
+    }
+
+    async fn unwrap(&self, wrapped: &WrappedKey) -> Result<Vec<u8>, KmsError> {
+        self.unwrap_key(wrapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::envelope::DataKey;
+
+    const MASTER_KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[actix_web::test]
+    // Made with the AESGCM of pyca/cryptography and a fixed nonce
+    async fn test_unwrap() {
+        let key_manager: InMemoryKeyManager = format!("1:{}", MASTER_KEY).parse().unwrap();
+        let wrapped: WrappedKey = "v1.1.AAECAwQFBgcICQoLBUCUWYengFnPA9XJ86s6L8GUxXayOR0+eiWnx18rQvDn4vUkpvyNU3F87GQx0IbD".parse().unwrap();
+        assert_eq!(key_manager.unwrap(&wrapped).await.unwrap(), [0x42; 32]);
+        // the version is authenticated
+        let mut other_keys = key_manager.keys().clone();
+        other_keys.insert(2, other_keys[&1]);
+        let other = InMemoryKeyManager::from_keys(other_keys).unwrap();
+        assert_eq!(other.unwrap(&WrappedKey{ version: 2, ..wrapped }).await.err(), Some(KmsError::UnwrapFailed));
+    }
+
+    #[actix_web::test]
+    async fn test_wrap() {
+        let mut key_manager: InMemoryKeyManager = format!("1:{},2:{}", MASTER_KEY, "11".repeat(32)).parse().unwrap();
+        assert_eq!(key_manager.current_version(), 2);
+        let data_key = DataKey::new();
+        let wrapped = key_manager.wrap(data_key.as_bytes()).await.unwrap();
+        assert_eq!(wrapped.version, 2);
+        assert_eq!(key_manager.unwrap(&wrapped).await.unwrap(), data_key.as_bytes());
+
+        assert_eq!(key_manager.add_version(), 3);
+        assert_eq!(key_manager.wrap(data_key.as_bytes()).await.unwrap().version, 3);
+        assert_eq!(key_manager.unwrap(&wrapped).await.unwrap(), data_key.as_bytes());
+
+        let old: InMemoryKeyManager = format!("1:{}", MASTER_KEY).parse().unwrap();
+        assert_eq!(old.unwrap(&wrapped).await.err(), Some(KmsError::UnknownMasterKey(2)));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!("".parse::<InMemoryKeyManager>().is_err());
+        assert!("1:00".parse::<InMemoryKeyManager>().is_err());
+        assert!(MASTER_KEY.parse::<InMemoryKeyManager>().is_err());
+        assert!(format!("1:{},1:{}", MASTER_KEY, MASTER_KEY).parse::<InMemoryKeyManager>().is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use async_trait::async_trait;
+use crate::crypto::envelope::WrappedKey;
+use in_memory::InMemoryKeyManager;
+use local_file::LocalFileKeyManager;
+
+pub mod in_memory;
+pub mod local_file;
+
+#[derive(Debug, PartialEq)]
+pub enum KmsError {
+    UnknownMasterKey(u32),
+    UnwrapFailed,
+}
+
+impl fmt::Display for KmsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KmsError::UnknownMasterKey(version) => write!(f, "Master key version {} is not available", version),
+            KmsError::UnwrapFailed => write!(f, "Data key could not be unwrapped"),
+        }
+    }
+}
+
+impl std::error::Error for KmsError {}
+
+// Where the master keys live. Master keys never leave the key manager:
+// it only wraps and unwraps the data keys of records (see crypto::envelope).
+// Implement this to keep master keys in an HSM or a cloud KMS.
+#[async_trait]
+pub trait KeyManager: Send + Sync {
+    // Version of the master key wrapping new data keys
+    fn current_version(&self) -> u32;
+    async fn wrap(&self, data_key: &[u8]) -> Result<WrappedKey, KmsError>;
+    async fn unwrap(&self, wrapped: &WrappedKey) -> Result<Vec<u8>, KmsError>;
+}
+
+// MASTER_KEY_FILE and MASTER_KEY_PASSPHRASE for a local PKCS#8 key file,
+// or else MASTER_KEYS=1:<hex of 32 bytes>,... held in memory
+pub fn from_env() -> Result<Box<dyn KeyManager>, Box<dyn Error>> {
+    dotenv::dotenv().ok();
+    if let Ok(path) = env::var("MASTER_KEY_FILE") {
+        let passphrase = env::var("MASTER_KEY_PASSPHRASE").map_err(|_| "MASTER_KEY_FILE needs MASTER_KEY_PASSPHRASE")?;
+        return Ok(Box::new(LocalFileKeyManager::open(&path, &passphrase)?));
+    }
+    let keys = env::var("MASTER_KEYS").map_err(|_| "Either MASTER_KEY_FILE or MASTER_KEYS must be set")?;
+    Ok(Box::new(keys.parse::<InMemoryKeyManager>()?))
+}
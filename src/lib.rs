@@ -0,0 +1,8 @@
+// Keypairs and chain signing formats. Routes use part of this API; the rest
+// (key generation, verification, other export formats) is there for callers
+// outside the server, so it is a library rather than a module of the binary.
+
+// new() makes a random key; a Default doing that would be a surprise
+#![allow(clippy::new_without_default)]
+
+pub mod crypto;
@@ -1,18 +1,34 @@
-use actix_web::{get, middleware, web, App, HttpServer, Responder};
-use entity::user;
-use migration::{Migrator, MigratorTrait};
-use sea_orm::{Database, DatabaseConnection, ConnectOptions};
+use actix_web::{middleware, web, App, HttpServer};
+use clap::{Parser, Subcommand};
 use utils::app_state::AppState;
+use routes::{handler, github_handler::Github, google_handler::Google};
 use std::sync::Arc;
+// The library target, so routes reach it as crate::crypto
+use oauth_account_backend::crypto;
 
 mod utils;
 mod routes;
 mod init;
-mod crypto;
+mod kms;
+mod admin;
 
 
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+// Without a command, serve HTTP
+#[derive(Subcommand)]
+enum Command {
+    #[command(subcommand, about = "Maintenance tasks")]
+    Admin(admin::AdminCommand),
+}
+
 #[actix_web::main] // or #[tokio::main]
 async fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
 
     if std::env::var_os("RUST_LOG").is_none() {
         std::env::set_var("RUST_LOG", "actix_web=info");
@@ -22,24 +38,43 @@ async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
     env_logger::init();
 
+    // Fail on a bad network setting before serving or running a command,
+    // not on the first request that reads it
+    lazy_static::initialize(&utils::constants::NETWORK);
+    lazy_static::initialize(&utils::constants::ETHEREUM_CHAIN_ID);
+    lazy_static::initialize(&utils::constants::NEO_NETWORK_MAGIC);
+
+    if let Some(Command::Admin(command)) = cli.command {
+        if let Err(e) = admin::run(command).await {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-    let port = (*utils::constants::PORT).clone();
+    let port = *utils::constants::PORT;
     let address = (*utils::constants::ADDRESS).clone();
     let database_url = (*utils::constants::DATABASE_URL).clone();
-    let app_state = AppState::new(&database_url).await;
+    let key_manager = kms::from_env().expect("Failed to load the master keys");
+    let app_state = AppState::new(&database_url, key_manager).await;
     let arc_app_state = Arc::new(app_state);
     
-    init::create_tables_if_not_exists(&arc_app_state.db, user::Entity).await;
-    // init::create_tables_if_not_exists(&arc_app_state.db, private_key::Entity).await;
-    // Migrator::up(&db, None).await.unwrap();
+    if let Err(e) = init::migrate(&arc_app_state.db, *utils::constants::RUN_MIGRATIONS).await {
+        panic!("Failed to migrate the database: {}", e);
+    }
+    match init::encrypt_plaintext_users(&arc_app_state.db, &*arc_app_state.key_manager).await {
+        Ok(0) => (),
+        Ok(count) => log::info!("Encrypted the keys of {} users", count),
+        Err(e) => panic!("Failed to encrypt stored keys: {}", e),
+    }
 
     HttpServer::new(move || {
         App::new()
         .wrap(middleware::NormalizePath::trim())
-        .data(arc_app_state.clone())
+        .app_data(web::Data::new(arc_app_state.clone()))
         .wrap(middleware::Logger::default())
-        .configure(routes::github_handler::config)
-        .configure(routes::google_handler::config)
+        .service(handler::scope::<Github>().configure(handler::link_config::<Github, Google>))
+        .service(handler::scope::<Google>().configure(handler::link_config::<Google, Github>))
     })
     .bind((address, port))?
     .run()
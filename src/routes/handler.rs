@@ -1,3 +1,471 @@
-pub trait OAuthHandler {
-    fn get_account_id(token: &str) -> String;
-}
\ No newline at end of file
+use actix_web::{web, Either, HttpRequest, HttpResponse, Responder, Scope};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, ModelTrait, QueryFilter, SqlErr};
+use sea_orm::prelude::Uuid;
+use sea_orm::ActiveValue::Set;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sea_orm::Value;
+use crate::utils::{app_state::AppState, auth::get_bearer_token, err_message::ErrMessage};
+use crate::utils::constants::{ALLOW_PRIVATE_KEY_EXPORT, NETWORK};
+use crate::kms::KeyManager;
+use crate::utils::user_secrets::UserSecrets;
+use crate::crypto::network::Network;
+use super::sign_handler;
+use crate::crypto::{bitcoin_keypair::BitcoinKeypair, ethereum_keypair::EthereumKeypair, neo_keypair::NeoKeypair};
+use crate::crypto::chain_keypair::{self, account_keypair, ChainKeypair, KeySource, CHAINS};
+use crate::crypto::bip39::{mnemonic_to_seed, new_mnemonic, normalize_mnemonic};
+use entity::user;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::error::Error;
+
+// An OAuth identity provider whose users can own an account.
+// Implement this once and register it with `scope::<P>` in main.rs.
+pub trait OAuthProvider: 'static {
+    // Path segment serving this provider, e.g. "github" for /github
+    const NAME: &'static str;
+    // Header carrying the provider's access token, e.g. "X-Github"
+    const HEADER_KEY: &'static str;
+    // Column of the user table storing the provider's account id
+    const ID_COLUMN: user::Column;
+
+    // Call the provider's API with the token and get the user id
+    async fn get_account_id(token: &str) -> Result<String, Box<dyn Error>>;
+}
+
+// Random (v4), so ids say nothing about when or in which order accounts were made
+fn new_account_id() -> Uuid {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    uuid::Builder::from_random_bytes(bytes).into_uuid()
+}
+
+// What GET /{P} tells about an account. Never contains the private key.
+#[derive(Serialize)]
+pub struct AccountInfo {
+    pub id: Uuid,
+    pub google_id: Option<String>,
+    pub github_id: Option<String>,
+}
+
+impl From<user::Model> for AccountInfo {
+    fn from(user: user::Model) -> AccountInfo {
+        AccountInfo{ id: user.id, google_id: user.google_id, github_id: user.github_id }
+    }
+}
+
+// Public keys and addresses derived from the stored private key
+#[derive(Serialize)]
+pub struct PublicKeyInfo {
+    pub public_key: String,  // secp256k1 of Bitcoin; Ethereum shares it on accounts made before HD derivation
+    pub neo_public_key: String,  // P-256
+    pub bitcoin_address: String,
+    pub bitcoin_segwit_address: String,
+    pub bitcoin_nested_segwit_address: String,
+    pub bitcoin_taproot_address: String,
+    pub ethereum_address: String,
+    pub neo_address: String,
+}
+
+impl PublicKeyInfo {
+    pub fn from_key_source(source: &KeySource, network: Network) -> Result<PublicKeyInfo, Box<dyn Error>> {
+        let bitcoin = account_keypair::<BitcoinKeypair>(source, 0, network)?;
+        let ethereum = account_keypair::<EthereumKeypair>(source, 0, network)?;
+        let neo = account_keypair::<NeoKeypair>(source, 0, network)?;
+        Ok(PublicKeyInfo{
+            public_key: bitcoin.public_key,
+            neo_public_key: neo.public_key,
+            bitcoin_address: bitcoin.address,
+            bitcoin_segwit_address: bitcoin.segwit_address,
+            bitcoin_nested_segwit_address: bitcoin.nested_segwit_address,
+            bitcoin_taproot_address: bitcoin.taproot_address,
+            ethereum_address: ethereum.address,
+            neo_address: neo.address,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct ChainAddress {
+    pub public_key: String,
+    pub address: String,
+    // Every address type of the key, for chains with more than one (Bitcoin)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub addresses: BTreeMap<&'static str, String>,
+}
+
+// Addresses of the stored private key on every chain of the registry
+#[derive(Serialize)]
+pub struct ChainAddresses(pub BTreeMap<&'static str, ChainAddress>);
+
+impl ChainAddresses {
+    pub fn from_key_source(source: &KeySource, account: u32, network: Network) -> Result<ChainAddresses, Box<dyn Error>> {
+        let mut addresses = BTreeMap::new();
+        for chain in CHAINS {
+            let keypair = (chain.account_keypair)(source, account, network)?;
+            addresses.insert(chain.id, ChainAddress{
+                public_key: keypair.public_key().to_string(),
+                address: keypair.address().to_string(),
+                addresses: keypair.addresses(),
+            });
+        }
+        Ok(ChainAddresses(addresses))
+    }
+}
+
+// ?account=1 for another account of the same seed
+#[derive(Deserialize)]
+pub struct AccountQuery {
+    #[serde(default)]
+    pub account: u32,
+}
+
+pub async fn key_source(user: &user::Model, key_manager: &dyn KeyManager) -> Result<KeySource, Box<dyn Error>> {
+    UserSecrets::decrypt(user, key_manager).await?.key_source()
+}
+
+// The user's keypair of chain K at account index `account`
+pub async fn user_keypair<K: ChainKeypair>(user: &user::Model, key_manager: &dyn KeyManager, account: u32) -> Result<K, HttpResponse> {
+    let source = match key_source(user, key_manager).await {
+        Ok(s) => s,
+        Err(e) => return Err(HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None})),
+    };
+    account_keypair::<K>(&source, account, *NETWORK)
+        .map_err(|e| HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}))
+}
+
+// Providers return the account id either as a JSON string (Google "sub")
+// or as a JSON number (GitHub "id"). Never keep the quotes of a JSON string.
+pub fn account_id_from_json(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::String(s) => Some(s.to_owned()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// Read the token from P::HEADER_KEY and resolve it to P's account id
+pub async fn authenticate<P: OAuthProvider>(req: &HttpRequest) -> Result<String, HttpResponse> {
+    let token: String = match get_bearer_token(req, P::HEADER_KEY) {
+        Either::Right(err_resp) => return Err(err_resp),
+        Either::Left(token) => token,
+    };
+    match P::get_account_id(&token).await {
+        Ok(i) => Ok(i),
+        Err(_) => Err(HttpResponse::Unauthorized().content_type("application/json").json(ErrMessage{err: "Invalid token".to_string(), public_key: None})),
+    }
+}
+
+pub async fn find_user<P: OAuthProvider>(db: &DatabaseConnection, account_id: &str) -> Result<Option<user::Model>, DbErr> {
+    user::Entity::find()
+    .filter(P::ID_COLUMN.eq(account_id))
+    .one(db)
+    .await
+}
+
+// Resolve the token in `req` to the registered account of P
+pub async fn find_authenticated_user<P: OAuthProvider>(req: &HttpRequest, db: &DatabaseConnection) -> Result<user::Model, HttpResponse> {
+    let account_id = authenticate::<P>(req).await?;
+    match find_user::<P>(db, &account_id).await {
+        Ok(v) => match v {
+            Some(s) => Ok(s),
+            None => Err(HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "Not registered".to_string(), public_key: None})),
+        },
+        Err(e) => Err(HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None})),
+    }
+}
+
+// X-Github: gho...
+// X-Google: ya29....
+pub async fn get_account<P: OAuthProvider>(req: HttpRequest, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    HttpResponse::Ok().json(AccountInfo::from(user))
+}
+
+// GET /{P}/public
+pub async fn get_public_key<P: OAuthProvider>(req: HttpRequest, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    match key_source(&user, &*state.key_manager).await.and_then(|source| PublicKeyInfo::from_key_source(&source, *NETWORK)) {
+        Ok(info) => HttpResponse::Ok().json(info),
+        Err(e) => HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    }
+}
+
+// GET /{P}/addresses?account=0
+pub async fn get_addresses<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let source = match key_source(&user, &*state.key_manager).await {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    match ChainAddresses::from_key_source(&source, query.account, *NETWORK) {
+        Ok(addresses) => HttpResponse::Ok().json(addresses),
+        Err(e) => HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    }
+}
+
+// ?chain=solana&account=0 to export one chain's key instead of the whole wallet
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub chain: Option<String>,
+    #[serde(default)]
+    pub account: u32,
+}
+
+// POST /{P}/export
+// The only endpoint returning the private key: the BIP-39 mnemonic of HD accounts
+// (without the passphrase, which was never stored), the WIF of older ones.
+// With ?chain=, the key of that chain in the format its wallets import
+// (WIF, hex, the 64-byte base58 keypair of Solana, suiprivkey, ...).
+// Disabled unless ALLOW_PRIVATE_KEY_EXPORT=true, and never cached by clients.
+pub async fn export_private_key<P: OAuthProvider>(req: HttpRequest, query: web::Query<ExportQuery>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    if !*ALLOW_PRIVATE_KEY_EXPORT {
+        return HttpResponse::Forbidden().content_type("application/json").json(ErrMessage{err: "Private key export disabled".to_string(), public_key: None});
+    }
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let secrets = match UserSecrets::decrypt(&user, &*state.key_manager).await {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let exported = match &query.chain {
+        Some(chain) => match export_chain_key(&secrets, chain, query.account) {
+            Ok(k) => k,
+            Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+        },
+        None => secrets.mnemonic.or(secrets.hd_seed).unwrap_or(secrets.private_key),
+    };
+    HttpResponse::Ok().insert_header(("Cache-Control", "no-store")).json(exported)
+}
+
+fn export_chain_key(secrets: &UserSecrets, chain: &str, account: u32) -> Result<String, Box<dyn Error>> {
+    let chain = chain_keypair::chain(chain)?;
+    let keypair = (chain.account_keypair)(&secrets.key_source()?, account, *NETWORK)?;
+    Ok(keypair.export_secret_key())
+}
+
+// Optional body of POST /{P}. Without a mnemonic, a new one of `words` words is made.
+// {"mnemonic": "abandon ... about", "passphrase": "TREZOR"}
+// {"words": 12}
+#[derive(Deserialize, Default)]
+pub struct CreateAccountRequest {
+    pub mnemonic: Option<String>,
+    #[serde(default)]
+    pub passphrase: String,
+    pub words: Option<usize>,
+}
+
+impl CreateAccountRequest {
+    // The normalized mnemonic and its seed
+    pub fn mnemonic_and_seed(&self) -> Result<(String, [u8; 64]), Box<dyn Error>> {
+        let mnemonic = match &self.mnemonic {
+            Some(m) => normalize_mnemonic(m)?,
+            None => new_mnemonic(self.words.unwrap_or(24))?,
+        };
+        let seed = mnemonic_to_seed(&mnemonic, &self.passphrase);
+        Ok((mnemonic, seed))
+    }
+}
+
+// POST /{P}
+pub async fn create_account<P: OAuthProvider>(req: HttpRequest, body: web::Bytes, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let account_id = match authenticate::<P>(&req).await {
+        Ok(i) => i,
+        Err(err_resp) => return err_resp,
+    };
+    let request: CreateAccountRequest = match body.is_empty() {
+        true => CreateAccountRequest::default(),
+        false => match serde_json::from_slice(&body) {
+            Ok(r) => r,
+            Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+        },
+    };
+    let (mnemonic, seed) = match request.mnemonic_and_seed() {
+        Ok(v) => v,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let db_pool = &state.db;
+    let source = match find_user::<P>(db_pool, &account_id).await {
+        Ok(v) => match v {
+            Some(_) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "Already registered".to_string(), public_key: None}),
+            None => {
+                let source = KeySource::Seed(seed.to_vec());
+                // The primary key; HD accounts store their first Bitcoin key there
+                let wif = match account_keypair::<BitcoinKeypair>(&source, 0, *NETWORK) {
+                    Ok(k) => k.secret_key_compressed_wif,
+                    Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+                };
+                let secrets = UserSecrets{ private_key: wif, hd_seed: Some(hex::encode(seed)), mnemonic: Some(mnemonic) };
+                let mut user_db = match secrets.encrypt(&*state.key_manager).await {
+                    Ok(m) => m,
+                    Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+                };
+                user_db.id = Set(new_account_id());
+                user_db.set(P::ID_COLUMN, Some(account_id).into());
+                if let Err(e) = user_db.insert(db_pool).await {
+                    // Another request registered the same account id in the meantime
+                    if let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() {
+                        return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "Already registered".to_string(), public_key: None});
+                    }
+                    log::error!("Failed to insert a user: {}", e);
+                    return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: "Failed to create the account".to_string(), public_key: None});
+                }
+                source
+            },
+        },
+        Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    match PublicKeyInfo::from_key_source(&source, *NETWORK) {
+        Ok(info) => HttpResponse::Ok().json(info),
+        Err(e) => HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    }
+}
+
+// POST /{P}/link/{Q}
+// Link Q's identity to the account of P's identity.
+// Requires valid tokens of both P (current) and Q (new).
+pub async fn link_identity<P: OAuthProvider, Q: OAuthProvider>(req: HttpRequest, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let new_account_id = match authenticate::<Q>(&req).await {
+        Ok(i) => i,
+        Err(err_resp) => return err_resp,
+    };
+    if user.get(Q::ID_COLUMN) != Value::String(None) {
+        return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "Already linked".to_string(), public_key: None});
+    }
+    match find_user::<Q>(db_pool, &new_account_id).await {
+        Ok(v) => if v.is_some() {
+            return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "Already registered".to_string(), public_key: None});
+        },
+        Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let mut user_db: user::ActiveModel = user.into();
+    user_db.set(Q::ID_COLUMN, Some(new_account_id).into());
+    let user = match user_db.update(db_pool).await {
+        Ok(u) => u,
+        Err(e) => {
+            if let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() {
+                return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "Already registered".to_string(), public_key: None});
+            }
+            return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None});
+        },
+    };
+    HttpResponse::Ok().json(AccountInfo::from(user))
+}
+
+// DELETE /{P}/link
+// Remove P's identity from its account. The last remaining identity is
+// never removed, otherwise nobody could reach the account again.
+pub async fn unlink_identity<P: OAuthProvider>(req: HttpRequest, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    if user.linked_identities() <= 1 {
+        return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "Cannot unlink the last identity".to_string(), public_key: None});
+    }
+    let mut user_db: user::ActiveModel = user.into();
+    user_db.set(P::ID_COLUMN, Value::String(None));
+    let user = match user_db.update(db_pool).await {
+        Ok(u) => u,
+        Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    HttpResponse::Ok().json(AccountInfo::from(user))
+}
+
+pub fn config<P: OAuthProvider>(config: &mut web::ServiceConfig){
+    config
+    .route("", web::get().to(get_account::<P>))
+    .route("", web::post().to(create_account::<P>))
+    .route("/public", web::get().to(get_public_key::<P>))
+    .route("/addresses", web::get().to(get_addresses::<P>))
+    .route("/export", web::post().to(export_private_key::<P>))
+    .route("/link", web::delete().to(unlink_identity::<P>));
+}
+
+// Allow accounts of P to link an identity of Q
+pub fn link_config<P: OAuthProvider, Q: OAuthProvider>(config: &mut web::ServiceConfig){
+    config
+    .route(&format!("/link/{}", Q::NAME), web::post().to(link_identity::<P, Q>));
+}
+
+pub fn scope<P: OAuthProvider>() -> Scope {
+    web::scope(&format!("/{}", P::NAME))
+    .configure(config::<P>)
+    .configure(sign_handler::config::<P>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::solana_keypair::SolanaKeypair;
+
+    #[test]
+    fn test_account_id_from_json() {
+        let google: serde_json::Value = serde_json::from_str(r#"{"sub": "110169484474386276334"}"#).unwrap();
+        assert_eq!(account_id_from_json(&google["sub"]).unwrap(), "110169484474386276334");
+        let github: serde_json::Value = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+        assert_eq!(account_id_from_json(&github["id"]).unwrap(), "1");
+        assert!(account_id_from_json(&serde_json::Value::Null).is_none());
+    }
+
+    #[test]
+    fn test_public_key_info() {
+        let info = PublicKeyInfo::from_key_source(&KeySource::Legacy("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd".to_string()), Network::Mainnet).unwrap();
+        assert_eq!(info.bitcoin_address, "19GKxUAfpozs8RXuknuZBCAKvJw3SiNYcd");
+        assert_eq!(info.ethereum_address, "0x5cf4e71E0d8466A958934Ce4e0D00b8ed1A3A973");
+        assert_eq!(info.neo_address, "NUrR2m4hRyYTdzFseDZzUKLhj5YLJtjEN2");
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(!json.contains("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd"));
+    }
+
+    #[test]
+    fn test_chain_addresses() {
+        let addresses = ChainAddresses::from_key_source(&KeySource::Legacy("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd".to_string()), 0, Network::Mainnet).unwrap();
+        let json = serde_json::to_value(&addresses).unwrap();
+        assert_eq!(json["bitcoin"]["address"], "19GKxUAfpozs8RXuknuZBCAKvJw3SiNYcd");
+        assert_eq!(json["ethereum"]["address"], "0x5cf4e71E0d8466A958934Ce4e0D00b8ed1A3A973");
+        assert_eq!(json["neo"]["address"], "NUrR2m4hRyYTdzFseDZzUKLhj5YLJtjEN2");
+        assert_eq!(json["bitcoin"]["public_key"], json["ethereum"]["public_key"]);
+        assert_eq!(json["bitcoin"]["addresses"]["p2pkh"], "19GKxUAfpozs8RXuknuZBCAKvJw3SiNYcd");
+        assert!(json["bitcoin"]["addresses"]["p2tr"].as_str().unwrap().starts_with("bc1p"));
+        assert!(json["ethereum"].get("addresses").is_none());
+        assert_eq!(json["solana"]["address"], SolanaKeypair::from_compressed_wif("KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd").unwrap().address);
+    }
+
+    #[test]
+    fn test_create_account_request() {
+        let request: CreateAccountRequest = serde_json::from_str(r#"{"mnemonic": " Legal winner thank year wave sausage worth useful legal winner thank yellow ", "passphrase": "TREZOR"}"#).unwrap();
+        let (mnemonic, seed) = request.mnemonic_and_seed().unwrap();
+        assert_eq!(mnemonic, "legal winner thank year wave sausage worth useful legal winner thank yellow");
+        assert_eq!(hex::encode(seed), "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607");
+
+        let (mnemonic, _) = CreateAccountRequest::default().mnemonic_and_seed().unwrap();
+        assert_eq!(mnemonic.split(' ').count(), 24);
+        let request: CreateAccountRequest = serde_json::from_str(r#"{"words": 12}"#).unwrap();
+        assert_eq!(request.mnemonic_and_seed().unwrap().0.split(' ').count(), 12);
+        let request: CreateAccountRequest = serde_json::from_str(r#"{"words": 15}"#).unwrap();
+        assert!(request.mnemonic_and_seed().is_err());
+    }
+}
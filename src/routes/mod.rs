@@ -1,2 +1,4 @@
+pub mod handler;
 pub mod github_handler;
-pub mod google_handler;
\ No newline at end of file
+pub mod google_handler;
+pub mod sign_handler;
\ No newline at end of file
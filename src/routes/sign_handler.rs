@@ -0,0 +1,509 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use crate::crypto::{bitcoin_keypair::BitcoinKeypair, ethereum_keypair::EthereumKeypair, neo_keypair::NeoKeypair};
+use crate::crypto::aptos_keypair::AptosKeypair;
+use crate::crypto::bitcoin_psbt::Psbt;
+use crate::crypto::chain_keypair::{self, ChainKeypair};
+use crate::crypto::cosmos_keypair::{CosmosKeypair, SignDoc, DEFAULT_HRP};
+use crate::crypto::ethereum_transaction::sign_transaction;
+use crate::crypto::neo_transaction::NeoTransaction;
+use crate::crypto::sui_keypair::SuiKeypair;
+use crate::kms::KeyManager;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use crate::utils::{app_state::AppState, err_message::ErrMessage};
+use crate::utils::constants::{ETHEREUM_CHAIN_ID, NEO_NETWORK_MAGIC, NETWORK};
+use super::handler::{find_authenticated_user, key_source, user_keypair, AccountQuery, OAuthProvider};
+use entity::user;
+use std::sync::Arc;
+use std::error::Error;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageEncoding {
+    #[default]
+    Utf8,
+    Hex,
+}
+
+#[derive(Deserialize)]
+pub struct SignRequest {
+    pub chain: String,  // id in the chain registry
+    pub message: String,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+}
+
+#[derive(Serialize)]
+pub struct SignResponse {
+    pub chain: String,
+    pub public_key: String,
+    pub signature: String,  // hex of the chain's message signature
+}
+
+#[derive(Deserialize)]
+pub struct PersonalSignRequest {
+    pub message: String,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+}
+
+// Ethereum signatures are 65 bytes r||s||v
+#[derive(Serialize)]
+pub struct EthereumSignResponse {
+    pub address: String,
+    pub signature: String,  // 0x-prefixed hex of r||s||v
+}
+
+#[derive(Serialize)]
+pub struct EthereumTransactionResponse {
+    pub address: String,
+    pub hash: String,
+    pub raw_transaction: String,  // 0x-prefixed, ready for eth_sendRawTransaction
+}
+
+#[derive(Serialize)]
+pub struct NeoTransactionResponse {
+    pub address: String,
+    pub hash: String,
+    pub raw_transaction: String,  // base64, ready for sendrawtransaction
+}
+
+#[derive(Deserialize)]
+pub struct PsbtRequest {
+    pub psbt: String,  // base64
+}
+
+#[derive(Serialize)]
+pub struct PsbtResponse {
+    pub address: String,
+    pub psbt: String,  // base64, with our partial signatures added
+    pub signed_inputs: Vec<usize>,
+}
+
+// ?prefix=osmo for the address of another chain of the Cosmos SDK
+#[derive(Deserialize)]
+pub struct CosmosQuery {
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub account: u32,
+}
+
+// SignDoc as given by CosmJS: bytes in base64, account number as string or number
+#[derive(Deserialize)]
+pub struct CosmosDirectRequest {
+    pub body_bytes: String,
+    pub auth_info_bytes: String,
+    pub chain_id: String,
+    pub account_number: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct CosmosSignResponse {
+    pub address: String,
+    pub public_key: String,  // base64 of the compressed key, as in StdSignature
+    pub signature: String,  // base64 of r||s
+}
+
+// signMessage of Aptos wallets (AIP-62)
+#[derive(Deserialize)]
+pub struct AptosMessageRequest {
+    pub message: String,
+    pub nonce: String,
+}
+
+#[derive(Serialize)]
+pub struct AptosMessageResponse {
+    pub address: String,
+    pub full_message: String,  // what was signed
+    pub signature: String,  // 0x-prefixed hex
+}
+
+#[derive(Deserialize)]
+pub struct AptosTransactionRequest {
+    pub raw_transaction: String,  // hex of the BCS RawTransaction
+}
+
+#[derive(Serialize)]
+pub struct AptosTransactionResponse {
+    pub address: String,
+    pub public_key: String,  // 0x-prefixed hex, for the Ed25519 authenticator
+    pub signature: String,  // 0x-prefixed hex
+}
+
+#[derive(Deserialize)]
+pub struct SuiTransactionRequest {
+    pub transaction: String,  // base64 of the BCS TransactionData
+}
+
+#[derive(Serialize)]
+pub struct SuiTransactionResponse {
+    pub address: String,
+    pub signature: String,  // base64 of flag || signature || public key, for sui_executeTransactionBlock
+}
+
+impl CosmosDirectRequest {
+    pub fn sign_doc(&self) -> Result<SignDoc, Box<dyn Error>> {
+        let account_number = match &self.account_number {
+            serde_json::Value::Number(n) => n.as_u64().ok_or("Invalid account_number")?,
+            serde_json::Value::String(s) => s.parse::<u64>()?,
+            _ => return Err("Invalid account_number".into()),
+        };
+        Ok(SignDoc{
+            body_bytes: BASE64.decode(&self.body_bytes)?,
+            auth_info_bytes: BASE64.decode(&self.auth_info_bytes)?,
+            chain_id: self.chain_id.to_owned(),
+            account_number,
+        })
+    }
+}
+
+fn decode_message(message: &str, encoding: MessageEncoding) -> Result<Vec<u8>, Box<dyn Error>> {
+    match encoding {
+        MessageEncoding::Utf8 => Ok(message.as_bytes().to_vec()),
+        MessageEncoding::Hex => Ok(hex::decode(message.trim_start_matches("0x"))?),
+    }
+}
+
+impl SignRequest {
+    pub fn message_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        decode_message(&self.message, self.encoding)
+    }
+}
+
+impl PersonalSignRequest {
+    pub fn message_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        decode_message(&self.message, self.encoding)
+    }
+}
+
+impl SignResponse {
+    // Sign with the stored key. The private key never leaves the server.
+    pub fn sign(chain: &str, keypair: &dyn ChainKeypair, message: &[u8]) -> Result<SignResponse, Box<dyn Error>> {
+        let signature = keypair.sign(message)?;
+        Ok(SignResponse{ chain: chain.to_string(), public_key: keypair.public_key().to_string(), signature: hex::encode(signature) })
+    }
+}
+
+// POST /{P}/sign?account=0
+// Signs in the chain's message format (Bitcoin Signed Message, EIP-191, ...),
+// so the result can't be used as a transaction signature.
+// {"chain": "neo", "message": "hello"}
+// {"chain": "ethereum", "message": "0x68656c6c6f", "encoding": "hex"}
+pub async fn sign<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<SignRequest>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let chain = match chain_keypair::chain(&body.chain) {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let message = match body.message_bytes() {
+        Ok(m) => m,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let source = match key_source(&user, &*state.key_manager).await {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::InternalServerError().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let keypair = match (chain.account_keypair)(&source, query.account, *NETWORK) {
+        Ok(k) => k,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    match SignResponse::sign(chain.id, &*keypair, &message) {
+        Ok(signed) => HttpResponse::Ok().json(signed),
+        Err(e) => HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    }
+}
+
+// POST /{P}/sign/ethereum/personal?account=0
+// EIP-191 personal_sign
+// {"message": "hello"}
+pub async fn sign_ethereum_personal<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<PersonalSignRequest>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let message = match body.message_bytes() {
+        Ok(m) => m,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let keypair = match user_keypair::<EthereumKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let signature = keypair.sign_personal_message(&message);
+    HttpResponse::Ok().json(EthereumSignResponse{ address: keypair.address, signature: format!("0x{}", hex::encode(signature)) })
+}
+
+// POST /{P}/sign/ethereum/typed-data?account=0
+// EIP-712 eth_signTypedData_v4. The body is the typed data itself:
+// {"types": {...}, "primaryType": "Mail", "domain": {...}, "message": {...}}
+pub async fn sign_ethereum_typed_data<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<serde_json::Value>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match user_keypair::<EthereumKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let signature = match keypair.sign_typed_data(&body) {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    HttpResponse::Ok().json(EthereumSignResponse{ address: keypair.address, signature: format!("0x{}", hex::encode(signature)) })
+}
+
+// POST /{P}/sign/ethereum/transaction?account=0
+// Legacy (EIP-155) or EIP-1559 transaction; nonce and fees are up to the client.
+// chainId defaults to ETHEREUM_CHAIN_ID of the deployment.
+// {"nonce": 0, "maxPriorityFeePerGas": "1000000000", "maxFeePerGas": "30000000000", "gas": 21000,
+//  "to": "0x...", "value": "1000000000000000000", "data": "0x", "chainId": 1}
+pub async fn sign_ethereum_transaction<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<serde_json::Value>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match user_keypair::<EthereumKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let signed = match sign_transaction(&keypair, &body, *ETHEREUM_CHAIN_ID) {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    HttpResponse::Ok().json(EthereumTransactionResponse{
+        address: keypair.address,
+        hash: format!("0x{}", hex::encode(signed.hash)),
+        raw_transaction: format!("0x{}", hex::encode(signed.raw_transaction)),
+    })
+}
+
+// POST /{P}/sign/bitcoin/psbt?account=0
+// Signs the P2PKH and P2WPKH inputs paying to the user's key; finalizing is up to the client.
+// Every input to sign needs its non-witness UTXO, segwit ones included.
+// {"psbt": "cHNidP8BAH..."}
+pub async fn sign_bitcoin_psbt<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<PsbtRequest>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match user_keypair::<BitcoinKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let mut psbt = match BASE64.decode(body.psbt.trim()).map_err(|e| e.into()).and_then(|data| Psbt::from_bytes(&data)) {
+        Ok(p) => p,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let signed_inputs = match psbt.sign(&keypair) {
+        Ok(s) => s,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    if signed_inputs.is_empty() {
+        return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "No input belongs to this account".to_string(), public_key: None});
+    }
+    HttpResponse::Ok().json(PsbtResponse{ address: keypair.address, psbt: BASE64.encode(psbt.serialize()), signed_inputs })
+}
+
+// POST /{P}/sign/neo/transaction?account=0
+// Transaction in the JSON format of the Neo RPC, plus the network magic (NEO_NETWORK_MAGIC if omitted).
+// Signers default to the user's account with CalledByEntry.
+// {"nonce": 1, "sysfee": "997750", "netfee": "1230610", "validuntilblock": 5000000, "script": "<base64>", "network": 860833102}
+pub async fn sign_neo_transaction<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<serde_json::Value>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match user_keypair::<NeoKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let network = match body.get("network") {
+        None | Some(serde_json::Value::Null) => *NEO_NETWORK_MAGIC,
+        Some(n) => match n.as_u64().and_then(|n| u32::try_from(n).ok()) {
+            Some(n) => n,
+            None => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "network must be a u32 magic".to_string(), public_key: None}),
+        },
+    };
+    let mut tx = match NeoTransaction::from_json(&body, keypair.script_hash()) {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    if let Err(e) = keypair.sign_transaction(&mut tx, network) {
+        return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None});
+    }
+    HttpResponse::Ok().json(NeoTransactionResponse{
+        address: keypair.address.to_owned(),
+        hash: tx.hash_string(),
+        raw_transaction: BASE64.encode(tx.serialize()),
+    })
+}
+
+async fn cosmos_keypair(user: &user::Model, key_manager: &dyn KeyManager, query: &CosmosQuery) -> Result<CosmosKeypair, HttpResponse> {
+    let keypair = user_keypair::<CosmosKeypair>(user, key_manager, query.account).await?;
+    let hrp = query.prefix.as_deref().unwrap_or(DEFAULT_HRP);
+    match CosmosKeypair::from_secret_key_slice(&keypair.secret_key.secret_bytes(), hrp) {
+        Ok(k) => Ok(k),
+        Err(e) => Err(HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None})),
+    }
+}
+
+// POST /{P}/sign/cosmos/direct?prefix=osmo&account=0
+// SIGN_MODE_DIRECT
+// {"body_bytes": "<base64>", "auth_info_bytes": "<base64>", "chain_id": "cosmoshub-4", "account_number": "42"}
+pub async fn sign_cosmos_direct<P: OAuthProvider>(req: HttpRequest, query: web::Query<CosmosQuery>, body: web::Json<CosmosDirectRequest>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match cosmos_keypair(&user, &*state.key_manager, &query).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let sign_doc = match body.sign_doc() {
+        Ok(d) => d,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let signature = keypair.sign_direct(&sign_doc);
+    HttpResponse::Ok().json(CosmosSignResponse{
+        public_key: BASE64.encode(keypair.public_key_bytes()),
+        address: keypair.address,
+        signature: BASE64.encode(signature),
+    })
+}
+
+// POST /{P}/sign/cosmos/amino?prefix=osmo&account=0
+// SIGN_MODE_LEGACY_AMINO_JSON. The body is the StdSignDoc itself:
+// {"chain_id": "cosmoshub-4", "account_number": "42", "sequence": "0", "fee": {...}, "msgs": [...], "memo": ""}
+pub async fn sign_cosmos_amino<P: OAuthProvider>(req: HttpRequest, query: web::Query<CosmosQuery>, body: web::Json<serde_json::Value>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match cosmos_keypair(&user, &*state.key_manager, &query).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    if !body.is_object() {
+        return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: "StdSignDoc must be an object".to_string(), public_key: None});
+    }
+    let signature = keypair.sign_amino(&body);
+    HttpResponse::Ok().json(CosmosSignResponse{
+        public_key: BASE64.encode(keypair.public_key_bytes()),
+        address: keypair.address,
+        signature: BASE64.encode(signature),
+    })
+}
+
+// POST /{P}/sign/aptos/message?account=0
+// {"message": "hello", "nonce": "<chosen by the dapp>"}
+pub async fn sign_aptos_message<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<AptosMessageRequest>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match user_keypair::<AptosKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let signature = keypair.sign_message(&body.message, &body.nonce);
+    HttpResponse::Ok().json(AptosMessageResponse{
+        full_message: AptosKeypair::full_message(&body.message, &body.nonce),
+        address: keypair.address,
+        signature: format!("0x{}", hex::encode(signature)),
+    })
+}
+
+// POST /{P}/sign/aptos/transaction?account=0
+// {"raw_transaction": "0x<BCS RawTransaction>"}
+pub async fn sign_aptos_transaction<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<AptosTransactionRequest>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match user_keypair::<AptosKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let raw_transaction = match hex::decode(body.raw_transaction.trim_start_matches("0x")) {
+        Ok(t) => t,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let signature = keypair.sign_transaction(&raw_transaction);
+    HttpResponse::Ok().json(AptosTransactionResponse{
+        address: keypair.address,
+        public_key: format!("0x{}", keypair.public_key.to_lowercase()),
+        signature: format!("0x{}", hex::encode(signature)),
+    })
+}
+
+// POST /{P}/sign/sui/transaction?account=0
+// {"transaction": "<base64 TransactionData>"}
+pub async fn sign_sui_transaction<P: OAuthProvider>(req: HttpRequest, query: web::Query<AccountQuery>, body: web::Json<SuiTransactionRequest>, state: web::Data<Arc<AppState>>) -> impl Responder {
+    let db_pool = &state.db;
+    let user = match find_authenticated_user::<P>(&req, db_pool).await {
+        Ok(u) => u,
+        Err(err_resp) => return err_resp,
+    };
+    let keypair = match user_keypair::<SuiKeypair>(&user, &*state.key_manager, query.account).await {
+        Ok(k) => k,
+        Err(err_resp) => return err_resp,
+    };
+    let transaction = match BASE64.decode(&body.transaction) {
+        Ok(t) => t,
+        Err(e) => return HttpResponse::BadRequest().content_type("application/json").json(ErrMessage{err: e.to_string(), public_key: None}),
+    };
+    let signature = keypair.sign_transaction(&transaction);
+    HttpResponse::Ok().json(SuiTransactionResponse{ address: keypair.address, signature: BASE64.encode(signature) })
+}
+
+pub fn config<P: OAuthProvider>(config: &mut web::ServiceConfig){
+    config
+    .route("/sign", web::post().to(sign::<P>))
+    .route("/sign/ethereum/personal", web::post().to(sign_ethereum_personal::<P>))
+    .route("/sign/ethereum/typed-data", web::post().to(sign_ethereum_typed_data::<P>))
+    .route("/sign/ethereum/transaction", web::post().to(sign_ethereum_transaction::<P>))
+    .route("/sign/bitcoin/psbt", web::post().to(sign_bitcoin_psbt::<P>))
+    .route("/sign/neo/transaction", web::post().to(sign_neo_transaction::<P>))
+    .route("/sign/cosmos/direct", web::post().to(sign_cosmos_direct::<P>))
+    .route("/sign/cosmos/amino", web::post().to(sign_cosmos_amino::<P>))
+    .route("/sign/aptos/message", web::post().to(sign_aptos_message::<P>))
+    .route("/sign/aptos/transaction", web::post().to(sign_aptos_transaction::<P>))
+    .route("/sign/sui/transaction", web::post().to(sign_sui_transaction::<P>));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::network::Network;
+    use crate::crypto::solana_keypair::SolanaKeypair;
+
+    #[test]
+    fn test_sign_message() {
+        let wif = "KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd";
+        let request: SignRequest = serde_json::from_str(r#"{"chain": "neo", "message": "0x68656c6c6f", "encoding": "hex"}"#).unwrap();
+        assert_eq!(request.message_bytes().unwrap(), b"hello");
+        let neo = NeoKeypair::from_compressed_wif(wif).unwrap();
+        let signed = SignResponse::sign(&request.chain, &neo, &request.message_bytes().unwrap()).unwrap();
+        assert_eq!(signed.signature, hex::encode(neo.sign_message(b"hello")));
+        assert_eq!(signed.public_key, neo.public_key);
+        let bitcoin = BitcoinKeypair::from_compressed_wif(wif, Network::Mainnet).unwrap();
+        let ethereum = EthereumKeypair::from_compressed_wif(wif).unwrap();
+        assert_eq!(SignResponse::sign("bitcoin", &bitcoin, b"hello").unwrap().signature, hex::encode(bitcoin.sign_message(b"hello")));
+        assert_eq!(SignResponse::sign("ethereum", &ethereum, b"hello").unwrap().signature, hex::encode(ethereum.sign_personal_message(b"hello")));
+        let solana = SolanaKeypair::from_compressed_wif(wif).unwrap();
+        assert!(SignResponse::sign("solana", &solana, b"").is_err());
+    }
+}
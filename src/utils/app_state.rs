@@ -1,13 +1,14 @@
 use sea_orm::{Database, DatabaseConnection, ConnectOptions};
 use std::time::Duration;
-use crate::utils::constants;
+use crate::kms::KeyManager;
 
 pub struct AppState {
-    pub db: DatabaseConnection
+    pub db: DatabaseConnection,
+    pub key_manager: Box<dyn KeyManager>,
 }
 
 impl AppState {
-    pub async fn new(database_url: &str) -> Self {
+    pub async fn new(database_url: &str, key_manager: Box<dyn KeyManager>) -> Self {
         // let database_url = (*constants::DATABASE_URL).clone();
         let mut opt: ConnectOptions = ConnectOptions::new(database_url);
         opt.max_connections(32)
@@ -18,6 +19,6 @@ impl AppState {
             .sqlx_logging(true);
         let db: DatabaseConnection = Database::connect(opt).await.unwrap();
 
-        Self { db }
+        Self { db, key_manager }
     }
 }
\ No newline at end of file
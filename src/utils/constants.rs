@@ -1,11 +1,17 @@
 use std::env;
 
 use lazy_static::lazy_static;
+use crate::crypto::network::Network;
 
 lazy_static!{
     pub static ref ADDRESS: String = set_address();
     pub static ref DATABASE_URL: String = set_database_url();
     pub static ref PORT: u16 = set_port();
+    pub static ref ALLOW_PRIVATE_KEY_EXPORT: bool = set_allow_private_key_export();
+    pub static ref NETWORK: Network = set_network();
+    pub static ref ETHEREUM_CHAIN_ID: u64 = set_ethereum_chain_id();
+    pub static ref NEO_NETWORK_MAGIC: u32 = set_neo_network_magic();
+    pub static ref RUN_MIGRATIONS: bool = set_run_migrations();
 }
 
 
@@ -23,4 +29,42 @@ fn set_database_url() -> String {
 fn set_port() -> u16 {
     dotenv::dotenv().ok();
     env::var("PORT").unwrap().parse::<u16>().unwrap()
+}
+
+// Private key export is off unless explicitly enabled
+fn set_allow_private_key_export() -> bool {
+    dotenv::dotenv().ok();
+    env::var("ALLOW_PRIVATE_KEY_EXPORT").map(|v| v == "true").unwrap_or(false)
+}
+
+// mainnet, testnet or regtest; mainnet if not set
+fn set_network() -> Network {
+    dotenv::dotenv().ok();
+    match env::var("NETWORK") {
+        Ok(v) => v.parse::<Network>().unwrap_or_else(|_| panic!("NETWORK must be mainnet, testnet or regtest, not {:?}", v)),
+        Err(_) => Network::Mainnet,
+    }
+}
+
+fn set_ethereum_chain_id() -> u64 {
+    dotenv::dotenv().ok();
+    match env::var("ETHEREUM_CHAIN_ID") {
+        Ok(v) => v.parse::<u64>().unwrap_or_else(|_| panic!("ETHEREUM_CHAIN_ID must be a decimal u64, not {:?}", v)),
+        Err(_) => NETWORK.default_ethereum_chain_id(),
+    }
+}
+
+fn set_neo_network_magic() -> u32 {
+    dotenv::dotenv().ok();
+    match env::var("NEO_NETWORK_MAGIC") {
+        Ok(v) => v.parse::<u32>().unwrap_or_else(|_| panic!("NEO_NETWORK_MAGIC must be a decimal u32, not {:?}", v)),
+        Err(_) => NETWORK.default_neo_network_magic(),
+    }
+}
+
+// Pending migrations are applied at startup unless set to false.
+// Otherwise apply them with the migration crate: `cargo run -p migration -- up`
+fn set_run_migrations() -> bool {
+    dotenv::dotenv().ok();
+    env::var("RUN_MIGRATIONS").map(|v| v != "false").unwrap_or(true)
 }
\ No newline at end of file
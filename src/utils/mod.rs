@@ -1,5 +1,5 @@
 pub mod constants;
-pub mod api_response;
 pub mod app_state;
 pub mod auth;
-pub mod err_message;
\ No newline at end of file
+pub mod err_message;
+pub mod user_secrets;
\ No newline at end of file
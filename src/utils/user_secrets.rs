@@ -0,0 +1,92 @@
+use std::error::Error;
+use entity::user;
+use sea_orm::ActiveValue::Set;
+use sea_orm::IdenStatic;
+use crate::crypto::chain_keypair::KeySource;
+use crate::crypto::envelope::{DataKey, WrappedKey};
+use crate::kms::KeyManager;
+
+// The secret columns of a user row, in plaintext. Never stored as is.
+pub struct UserSecrets {
+    pub private_key: String,
+    pub hd_seed: Option<String>,
+    pub mnemonic: Option<String>,
+}
+
+impl UserSecrets {
+    // Rows without a data key predate encryption at rest and are still plaintext
+    pub async fn decrypt(user: &user::Model, key_manager: &dyn KeyManager) -> Result<UserSecrets, Box<dyn Error>> {
+        let Some(wrapped) = &user.data_key else {
+            return Ok(UserSecrets{ private_key: user.private_key.to_owned(), hd_seed: user.hd_seed.to_owned(), mnemonic: user.mnemonic.to_owned() });
+        };
+        let data_key = DataKey::from_slice(&key_manager.unwrap(&wrapped.parse::<WrappedKey>()?).await?)?;
+        let decrypt = |ciphertext: &str, column: user::Column| -> Result<String, Box<dyn Error>> {
+            Ok(String::from_utf8(data_key.decrypt(ciphertext, column.as_str())?)?)
+        };
+        Ok(UserSecrets{
+            private_key: decrypt(&user.private_key, user::Column::PrivateKey)?,
+            hd_seed: user.hd_seed.as_deref().map(|s| decrypt(s, user::Column::HdSeed)).transpose()?,
+            mnemonic: user.mnemonic.as_deref().map(|s| decrypt(s, user::Column::Mnemonic)).transpose()?,
+        })
+    }
+
+    // The secret columns encrypted with a new data key, the wrapped data key included
+    pub async fn encrypt(&self, key_manager: &dyn KeyManager) -> Result<user::ActiveModel, Box<dyn Error>> {
+        let data_key = DataKey::new();
+        let wrapped = key_manager.wrap(data_key.as_bytes()).await?;
+        let encrypt = |plaintext: &str, column: user::Column| data_key.encrypt(plaintext.as_bytes(), column.as_str());
+        Ok(user::ActiveModel{
+            private_key: Set(encrypt(&self.private_key, user::Column::PrivateKey)),
+            hd_seed: Set(self.hd_seed.as_deref().map(|s| encrypt(s, user::Column::HdSeed))),
+            mnemonic: Set(self.mnemonic.as_deref().map(|s| encrypt(s, user::Column::Mnemonic))),
+            data_key: Set(Some(wrapped.to_string())),
+            ..Default::default()
+        })
+    }
+
+    pub fn key_source(&self) -> Result<KeySource, Box<dyn Error>> {
+        match &self.hd_seed {
+            Some(seed) => Ok(KeySource::Seed(hex::decode(seed)?)),
+            None => Ok(KeySource::Legacy(self.private_key.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kms::in_memory::InMemoryKeyManager;
+    use sea_orm::prelude::Uuid;
+
+    #[actix_web::test]
+    async fn test_encrypt_decrypt() {
+        let key_manager = InMemoryKeyManager::new();
+        let secrets = UserSecrets{
+            private_key: "KyLkhT5K4zMGCFErLttxLS5GNNtyGE92JR1fYcX5qk5Q8aoRkyrd".to_string(),
+            hd_seed: Some("000102030405060708090a0b0c0d0e0f".to_string()),
+            mnemonic: None,
+        };
+        let encrypted = secrets.encrypt(&key_manager).await.unwrap();
+        let user = user::Model{
+            id: Uuid::nil(),
+            private_key: encrypted.private_key.unwrap(),
+            google_id: None,
+            github_id: Some("1".to_string()),
+            hd_seed: encrypted.hd_seed.unwrap(),
+            mnemonic: encrypted.mnemonic.unwrap(),
+            data_key: encrypted.data_key.unwrap(),
+        };
+        assert!(!user.private_key.contains(&secrets.private_key));
+        assert_eq!(user.mnemonic, None);
+        let decrypted = UserSecrets::decrypt(&user, &key_manager).await.unwrap();
+        assert_eq!(decrypted.private_key, secrets.private_key);
+        assert_eq!(decrypted.hd_seed, secrets.hd_seed);
+
+        // secrets cannot be moved between columns
+        let swapped = user::Model{ hd_seed: Some(user.private_key.to_owned()), ..user.clone() };
+        assert!(UserSecrets::decrypt(&swapped, &key_manager).await.is_err());
+
+        let plaintext = user::Model{ private_key: secrets.private_key.to_owned(), hd_seed: None, data_key: None, ..user };
+        assert_eq!(UserSecrets::decrypt(&plaintext, &key_manager).await.unwrap().private_key, secrets.private_key);
+    }
+}